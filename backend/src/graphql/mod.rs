@@ -1,5 +1,6 @@
 mod posts;
 
+use crate::FoilPool;
 use async_graphql::dataloader::DataLoader;
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql::{EmptyMutation, EmptySubscription, Schema};
@@ -12,7 +13,6 @@ use axum::{
 };
 use axum_macros::debug_handler;
 use posts::{FoilLoader, QueryPosts};
-use sqlx::{Pool, Postgres};
 
 pub fn queries() -> QueryPosts {
     posts::QueryPosts::default()
@@ -21,19 +21,22 @@ pub fn queries() -> QueryPosts {
 pub type FoilSchema = Schema<QueryPosts, EmptyMutation, EmptySubscription>;
 
 // 📈 Create the main Foil GraphQL Schema
-pub fn graphql_schema(postgres_pool: &Pool<Postgres>) -> FoilSchema {
+pub fn graphql_schema(foil_pool: &FoilPool) -> FoilSchema {
     Schema::build(queries(), EmptyMutation, EmptySubscription)
         .data(DataLoader::new(
-            FoilLoader::new(postgres_pool.clone()),
+            FoilLoader::new(foil_pool.clone()),
             tokio::spawn,
         ))
-        .data(postgres_pool.clone())
+        .data(foil_pool.clone())
         .finish()
 }
 
 // 📊 Main GraphQL Handler endpoint.
 #[debug_handler]
-pub async fn graphql_handler(schema: Extension<FoilSchema>, req: GraphQLRequest) -> GraphQLResponse {
+pub async fn graphql_handler(
+    schema: Extension<FoilSchema>,
+    req: GraphQLRequest,
+) -> GraphQLResponse {
     let gql_inner = req.into_inner();
     let gql_response = schema.execute(gql_inner).await;
     gql_response.into()
@@ -1,12 +1,13 @@
-use crate::clean_path_string;
 use async_graphql::dataloader::Loader;
 use async_graphql::futures_util::TryStreamExt;
-use async_graphql::{Context, FieldError, Object, Result, SimpleObject};
+use async_graphql::{ComplexObject, Context, FieldError, Object, Result, SimpleObject};
 use chrono::{DateTime, Utc};
+use foil_core::{clean_path_string, match_pool, permalink_glob_to_regex, FoilPool, SQLPost};
 use itertools::Itertools;
 use log::error;
 use serde::Serialize;
-use sqlx::{Pool, Postgres};
+use sqlx::types::Json;
+use sqlx::FromRow;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -15,17 +16,137 @@ pub fn err<'a, T>(msg: &'a str) -> Result<T, async_graphql::Error> {
 }
 
 /// 🎇 An author of a Foil post.
-#[derive(Debug, Serialize, SimpleObject, Clone, sqlx::Type)]
-#[sqlx(type_name = "author")]
+#[derive(Debug, Serialize, SimpleObject, Clone)]
 pub struct Author {
     pub name: String,
+    /// 🏷️ The URL-safe name of this author's page.
+    pub slug: String,
     pub email: String,
     pub url: String,
+    /// 🖼️ A URL to a picture of this author.
+    pub avatar: Option<String>,
+    /// 📝 A short biography of this author.
+    pub bio: Option<String>,
+    /// 🔗 Links to this author's profiles elsewhere.
+    pub socials: Vec<SocialLink>,
+}
+
+impl From<foil_core::Author> for Author {
+    fn from(author: foil_core::Author) -> Self {
+        Author {
+            name: author.name,
+            slug: author.slug,
+            email: author.email,
+            url: author.url,
+            avatar: author.avatar,
+            bio: author.bio,
+            socials: author.socials.into_iter().map(SocialLink::from).collect(),
+        }
+    }
+}
+
+/// 🔗 A link to an author's profile on another site.
+#[derive(Debug, Serialize, SimpleObject, Clone)]
+pub struct SocialLink {
+    /// The name of the site, such as GitHub.
+    pub name: String,
+    pub url: String,
+}
+
+impl From<foil_core::SocialLink> for SocialLink {
+    fn from(link: foil_core::SocialLink) -> Self {
+        SocialLink {
+            name: link.name,
+            url: link.url,
+        }
+    }
+}
+
+/// 🧑‍🎨 An author as stored in the `authors` table, along with how many posts list them.
+#[derive(FromRow)]
+struct SQLAuthor {
+    id: i32,
+    name: String,
+    slug: String,
+    email: String,
+    url: String,
+    avatar: Option<String>,
+    bio: Option<String>,
+    socials: Json<Vec<foil_core::SocialLink>>,
+    post_count: i64,
+}
+
+/// 🧑‍🎨 An author's page, with their profile and the posts they wrote.
+#[derive(Debug, Serialize, SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct AuthorPage {
+    #[graphql(skip)]
+    pub id: i32,
+    /// 🎇 Who this author is.
+    pub profile: Author,
+    /// 📚 How many published posts this author wrote.
+    pub post_count: i32,
 }
 
-#[derive(sqlx::Type, Clone)]
-#[sqlx(type_name = "_author")]
-pub struct SQLAuthors(pub Vec<Author>);
+impl From<SQLAuthor> for AuthorPage {
+    fn from(sql_author: SQLAuthor) -> Self {
+        AuthorPage {
+            id: sql_author.id,
+            profile: Author {
+                name: sql_author.name,
+                slug: sql_author.slug,
+                email: sql_author.email,
+                url: sql_author.url,
+                avatar: sql_author.avatar,
+                bio: sql_author.bio,
+                socials: sql_author
+                    .socials
+                    .0
+                    .into_iter()
+                    .map(SocialLink::from)
+                    .collect(),
+            },
+            post_count: sql_author.post_count as i32,
+        }
+    }
+}
+
+#[ComplexObject]
+impl AuthorPage {
+    /// 🗞️ The published posts this author wrote, newest first.
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<Post>> {
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        let (offset, limit) = page(offset, limit);
+        let sql_result: Vec<Post> = match_pool!(foil_pool, p => {
+            sqlx::query_as::<_, SQLPost>(include_str!("sql/author_posts.sql"))
+                .bind(self.id)
+                .bind(Utc::now())
+                .bind(&limit)
+                .bind(&offset)
+                .fetch(p)
+                .map_err(|x| {
+                    error!("Query failed: {}", x.to_string());
+                })
+                .map_ok(Post::from)
+                .try_collect()
+                .await
+                .unwrap_or(vec![])
+        });
+        Ok(sql_result)
+    }
+}
+
+/// 📄 Clamp a page of results, 10 by default, to at most 100 results within the first 10000.
+fn page(offset: Option<i32>, limit: Option<i32>) -> (i32, i32) {
+    let offset = offset.unwrap_or(0).clamp(0, 10000);
+    let limit = limit.unwrap_or(10).clamp(0, 100);
+    (offset, limit)
+}
 
 /// ✨ A Foil post schema for GraphQL.
 #[derive(Debug, Serialize, SimpleObject, Clone)]
@@ -52,28 +173,21 @@ pub struct Post {
     pub date_modified: DateTime<Utc>,
 }
 
-#[derive(sqlx::FromRow, Clone)]
-pub struct SQLPost {
-    /// 😎 ID for item.
-    pub id: i32,
-    /// 📎 Permalink (eg. https://<your-blog>/{permalink}).
-    pub permalink: String,
-    /// 👋 Name of this item.
-    pub title: String,
-    /// The author(s) of this post.
-    pub authors: SQLAuthors,
-    /// 📝 Short description (120-240 characters) about the post.
-    pub description: String,
-    /// 🔎 Search keywords for this post.
-    pub keywords: Vec<String>,
-    /// 📑 Cover image url for this post.
-    pub covers: Vec<String>,
-    /// Main javascript file for this post with a default react component export.
-    pub main: String,
-    /// ⏰ The time this post was published.
-    pub date_published: DateTime<Utc>,
-    /// ⏱️ The time this post was updated.
-    pub date_modified: DateTime<Utc>,
+impl From<SQLPost> for Post {
+    fn from(sql_post: SQLPost) -> Self {
+        Post {
+            id: sql_post.id,
+            permalink: sql_post.permalink,
+            title: sql_post.title,
+            authors: sql_post.authors.0.into_iter().map(Author::from).collect(),
+            description: sql_post.description,
+            keywords: sql_post.keywords,
+            covers: sql_post.covers,
+            main: sql_post.main,
+            date_published: sql_post.date_published,
+            date_modified: sql_post.date_modified,
+        }
+    }
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -85,33 +199,32 @@ impl std::fmt::Display for PostId {
 }
 
 pub struct FoilLoader {
-    pub pool: Pool<Postgres>,
+    pub pool: FoilPool,
 }
 
 impl FoilLoader {
-    pub fn new(postgres_pool: Pool<Postgres>) -> Self {
-        Self {
-            pool: postgres_pool,
-        }
+    pub fn new(foil_pool: FoilPool) -> Self {
+        Self { pool: foil_pool }
     }
 }
 
-
 impl Loader<PostId> for FoilLoader {
     type Value = Post;
     type Error = FieldError;
 
     async fn load(&self, keys: &[PostId]) -> Result<HashMap<PostId, Self::Value>, Self::Error> {
         let sql_query = format!(include_str!("sql/post_load.sql"), keys.iter().join(","));
-        let sql_postmap: HashMap<PostId, SQLPost> = sqlx::query_as(&sql_query)
-            .fetch(&self.pool)
-            .map_err(|x| {
-                error!("Query Failed: {}", x.to_string());
-            })
-            .map_ok(|user: SQLPost| (PostId(user.id), user))
-            .try_collect()
-            .await
-            .unwrap_or(HashMap::new());
+        let sql_postmap: HashMap<PostId, SQLPost> = match_pool!(&self.pool, p => {
+            sqlx::query_as(&sql_query)
+                .fetch(p)
+                .map_err(|x| {
+                    error!("Query Failed: {}", x.to_string());
+                })
+                .map_ok(|user: SQLPost| (PostId(user.id), user))
+                .try_collect()
+                .await
+                .unwrap_or(HashMap::new())
+        });
 
         let mut m: HashMap<PostId, Self::Value> = HashMap::new();
         for sql_post in sql_postmap {
@@ -120,21 +233,7 @@ impl Loader<PostId> for FoilLoader {
             if m.contains_key(&key) {
                 continue;
             } else {
-                m.insert(
-                    key,
-                    Post {
-                        id: sql_post.1.id,
-                        permalink: sql_post.1.permalink,
-                        title: sql_post.1.title,
-                        authors: sql_post.1.authors.0,
-                        description: sql_post.1.description,
-                        keywords: sql_post.1.keywords,
-                        covers: sql_post.1.covers,
-                        main: sql_post.1.main,
-                        date_published: sql_post.1.date_published,
-                        date_modified: sql_post.1.date_modified,
-                    },
-                );
+                m.insert(key, Post::from(sql_post.1));
             }
         }
 
@@ -165,29 +264,30 @@ impl QueryPosts {
                 return err(&err_str);
             }
         }
-        let postgres_pool: &Pool<Postgres> = ctx.data_opt().unwrap();
-        let cur_query = include_str!("sql/posts_from_permalinks.sql");
-        let sql_result: Vec<Post> = sqlx::query_as(&cur_query)
-            .bind(&permalinks)
-            .fetch(postgres_pool)
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        // SQLite has no arrays, so permalinks are bound as a JSON array instead.
+        let sql_posts: Result<Vec<SQLPost>, sqlx::Error> = match foil_pool {
+            FoilPool::Postgres(p) => {
+                sqlx::query_as(include_str!("sql/posts_from_permalinks.sql"))
+                    .bind(&permalinks)
+                    .fetch_all(p)
+                    .await
+            }
+            FoilPool::Sqlite(p) => {
+                sqlx::query_as(include_str!("sql/sqlite/posts_from_permalinks.sql"))
+                    .bind(Json(&permalinks))
+                    .fetch_all(p)
+                    .await
+            }
+        };
+        let sql_result: Vec<Post> = sql_posts
             .map_err(|x| {
                 error!("Query failed: {}", x.to_string());
             })
-            .map_ok(|sql_post: SQLPost| Post {
-                id: sql_post.id,
-                permalink: sql_post.permalink,
-                title: sql_post.title,
-                authors: sql_post.authors.0,
-                description: sql_post.description,
-                keywords: sql_post.keywords,
-                covers: sql_post.covers,
-                main: sql_post.main,
-                date_published: sql_post.date_published,
-                date_modified: sql_post.date_modified,
-            })
-            .try_collect()
-            .await
-            .unwrap_or(vec![]);
+            .unwrap_or(vec![])
+            .into_iter()
+            .map(Post::from)
+            .collect();
         Ok(sql_result)
     }
 
@@ -207,59 +307,30 @@ impl QueryPosts {
             return err(&err_str);
         }
 
-        let postgres_pool: &Pool<Postgres> = ctx.data_opt().unwrap();
-        let cur_query = include_str!("sql/posts_from_glob.sql");
-        //Convert the following permalink to regex depending on if there's a star in it.
-        let mut permalink_regex = "^".to_string();
-        for char in permalink.chars() {
-            let char_string = char.to_string();
-            let char_str = &char_string;
-            match char {
-                '/' | '$' | '^' | '+' | '.' | '(' | ')' | '=' | '!' | '|' | ',' | '{' | '}'
-                | '[' | ']' => {
-                    permalink_regex += "\\";
-                    permalink_regex += char_str;
-                }
-                '*' => {
-                    permalink_regex += ".*";
-                }
-                _ => permalink_regex += char_str,
-            }
-        }
-        permalink_regex += "$";
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        let cur_query = if foil_pool.is_sqlite() {
+            include_str!("sql/sqlite/posts_from_glob.sql")
+        } else {
+            include_str!("sql/posts_from_glob.sql")
+        };
+        let permalink_regex = permalink_glob_to_regex(&permalink);
 
-        let mut offset = offset.unwrap_or(0);
-        if offset > 10000 {
-            offset = 10000;
-        }
-        let mut limit = limit.unwrap_or(10);
-        if limit > 100 {
-            limit = 100;
-        }
+        let (offset, limit) = page(offset, limit);
 
-        let sql_result: Vec<Post> = sqlx::query_as(&cur_query)
-            .bind(&permalink_regex)
-            .bind(&limit)
-            .bind(&offset)
-            .fetch(postgres_pool)
-            .map_err(|x| {
-                error!("Query failed: {}", x.to_string());
-            })
-            .map_ok(|sql_post: SQLPost| Post {
-                id: sql_post.id,
-                permalink: sql_post.permalink,
-                title: sql_post.title,
-                authors: sql_post.authors.0,
-                description: sql_post.description,
-                keywords: sql_post.keywords,
-                covers: sql_post.covers,
-                main: sql_post.main,
-                date_published: sql_post.date_published,
-                date_modified: sql_post.date_modified,
-            })
-            .try_collect()
-            .await
-            .unwrap_or(vec![]);
+        let sql_result: Vec<Post> = match_pool!(foil_pool, p => {
+            sqlx::query_as::<_, SQLPost>(&cur_query)
+                .bind(&permalink_regex)
+                .bind(&limit)
+                .bind(&offset)
+                .fetch(p)
+                .map_err(|x| {
+                    error!("Query failed: {}", x.to_string());
+                })
+                .map_ok(Post::from)
+                .try_collect()
+                .await
+                .unwrap_or(vec![])
+        });
         Ok(sql_result)
     }
 
@@ -272,7 +343,7 @@ impl QueryPosts {
             );
             return err(&err_str);
         }
-        let postgres_pool: &Pool<Postgres> = ctx.data_opt().unwrap();
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
         let path_pathbuf = PathBuf::from(permalink);
         let mut path_ancestors = path_pathbuf.ancestors();
         loop {
@@ -282,24 +353,15 @@ impl QueryPosts {
                     let par_path_buf = par.to_path_buf();
                     let par_clean = clean_path_string(&par_path_buf);
                     let cur_query = include_str!("sql/post_recursive_public.sql");
-                    let sql_result: Result<SQLPost, sqlx::Error> = sqlx::query_as(&cur_query)
-                        .bind(&par_clean)
-                        .fetch_one(postgres_pool)
-                        .await;
+                    let sql_result: Result<SQLPost, sqlx::Error> = match_pool!(foil_pool, p => {
+                        sqlx::query_as(&cur_query)
+                            .bind(&par_clean)
+                            .fetch_one(p)
+                            .await
+                    });
                     match sql_result {
                         Ok(sql_post) => {
-                            return Ok(Some(Post {
-                                id: sql_post.id,
-                                permalink: sql_post.permalink,
-                                title: sql_post.title,
-                                authors: sql_post.authors.0,
-                                description: sql_post.description,
-                                keywords: sql_post.keywords,
-                                covers: sql_post.covers,
-                                main: sql_post.main,
-                                date_published: sql_post.date_published,
-                                date_modified: sql_post.date_modified,
-                            }));
+                            return Ok(Some(Post::from(sql_post)));
                         }
                         Err(_sql_e) => (),
                     }
@@ -321,31 +383,89 @@ impl QueryPosts {
             );
             return err(&err_str);
         }
-        let postgres_pool: &Pool<Postgres> = ctx.data_opt().unwrap();
-        let cur_query = include_str!("sql/post_search.sql");
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        let cur_query = if foil_pool.is_sqlite() {
+            include_str!("sql/sqlite/post_search.sql")
+        } else {
+            include_str!("sql/post_search.sql")
+        };
         let sanitized_string = search_string.replace("%", "").replace("_", "");
         let str = "%".to_string() + &sanitized_string + "%";
-        let sql_result: Vec<Post> = sqlx::query_as(&cur_query)
-            .bind(&str)
-            .fetch(postgres_pool)
+        let sql_result: Vec<Post> = match_pool!(foil_pool, p => {
+            sqlx::query_as::<_, SQLPost>(&cur_query)
+                .bind(&str)
+                .fetch(p)
+                .map_err(|x| {
+                    error!("Query failed: {}", x.to_string());
+                })
+                .map_ok(Post::from)
+                .try_collect()
+                .await
+                .unwrap_or(vec![])
+        });
+        Ok(sql_result)
+    }
+
+    /// 🧑‍🎨 List every author of a published post, by name.
+    async fn authors(
+        &self,
+        ctx: &Context<'_>,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> Result<Vec<AuthorPage>> {
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        let (offset, limit) = page(offset, limit);
+        let sql_result: Vec<AuthorPage> = match_pool!(foil_pool, p => {
+            sqlx::query_as::<_, SQLAuthor>(include_str!("sql/authors.sql"))
+                .bind(Utc::now())
+                .bind(&limit)
+                .bind(&offset)
+                .fetch(p)
+                .map_err(|x| {
+                    error!("Query failed: {}", x.to_string());
+                })
+                .map_ok(AuthorPage::from)
+                .try_collect()
+                .await
+                .unwrap_or(vec![])
+        });
+        Ok(sql_result)
+    }
+
+    /// 🧑‍🎨 Find an author's page from either their name or their slug.
+    async fn author(
+        &self,
+        ctx: &Context<'_>,
+        name: Option<String>,
+        slug: Option<String>,
+    ) -> Result<Option<AuthorPage>> {
+        if name.is_some() == slug.is_some() {
+            return err("Find an author with either their name or their slug.");
+        }
+        for value in name.iter().chain(slug.iter()) {
+            if value.len() > 254 {
+                let err_str = format!(
+                    "Author names and slugs must have less than 254 characters. Check {}.",
+                    value
+                );
+                return err(&err_str);
+            }
+        }
+        let foil_pool: &FoilPool = ctx.data_opt().unwrap();
+        let slug = slug.map(|slug| slug.trim().to_lowercase());
+        let sql_result: Result<Option<SQLAuthor>, sqlx::Error> = match_pool!(foil_pool, p => {
+            sqlx::query_as(include_str!("sql/author.sql"))
+                .bind(&name)
+                .bind(&slug)
+                .bind(Utc::now())
+                .fetch_optional(p)
+                .await
+        });
+        let sql_author = sql_result
             .map_err(|x| {
                 error!("Query failed: {}", x.to_string());
             })
-            .map_ok(|sql_post: SQLPost| Post {
-                id: sql_post.id,
-                permalink: sql_post.permalink,
-                title: sql_post.title,
-                authors: sql_post.authors.0,
-                description: sql_post.description,
-                keywords: sql_post.keywords,
-                covers: sql_post.covers,
-                main: sql_post.main,
-                date_published: sql_post.date_published,
-                date_modified: sql_post.date_modified,
-            })
-            .try_collect()
-            .await
-            .unwrap_or(vec![]);
-        Ok(sql_result)
+            .unwrap_or(None);
+        Ok(sql_author.map(AuthorPage::from))
     }
 }
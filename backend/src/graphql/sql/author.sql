@@ -0,0 +1,7 @@
+SELECT authors.id, authors.name, authors.slug, authors.email, authors.url, authors.avatar, authors.bio, authors.socials, COUNT(post_authors.post_id) AS post_count
+FROM authors
+JOIN post_authors ON post_authors.author_id = authors.id
+JOIN posts ON posts.id = post_authors.post_id AND posts.date_published <= $3
+WHERE authors.name = $1 OR authors.slug = $2
+GROUP BY authors.id
+LIMIT 1 OFFSET 0
\ No newline at end of file
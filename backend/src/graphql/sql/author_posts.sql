@@ -0,0 +1,5 @@
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules
+FROM posts_with_authors
+WHERE id IN (SELECT post_id FROM post_authors WHERE author_id = $1) AND date_published <= $2
+ORDER BY date_published DESC
+LIMIT $3 OFFSET $4
\ No newline at end of file
@@ -1,3 +1,3 @@
-SELECT id, permalink, title, authors, description, keywords, covers, main, date_published, date_modified FROM posts
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules FROM posts_with_authors
 WHERE id IN ({})
 ORDER BY date_published
\ No newline at end of file
@@ -1,3 +0,0 @@
-SELECT root_path, permalink, assets, main
-FROM posts
-WHERE LOWER(permalink) = LOWER($1)
\ No newline at end of file
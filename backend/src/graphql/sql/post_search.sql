@@ -1,5 +1,5 @@
-SELECT id, permalink, title, authors, description, keywords, covers, main, date_published, date_modified
-FROM posts
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules
+FROM posts_with_authors
 WHERE LOWER(title) LIKE LOWER($1) or LOWER(description) LIKE LOWER($1) or array_to_string(keywords, ',') like LOWER($1)
 ORDER BY date_published DESC
 LIMIT 10 OFFSET 0
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules
+FROM posts_with_authors
+WHERE LOWER(permalink) IN (SELECT value FROM json_each($1))
+ORDER BY date_published DESC
+LIMIT 10 OFFSET 0
\ No newline at end of file
@@ -0,0 +1,126 @@
+use foil_core::pool::POSTS_CHANNEL;
+use foil_core::{redact_credentials, FoilPool};
+use log::{error, trace, warn};
+use sqlx::postgres::PgListener;
+use sqlx::{Pool, Postgres, Sqlite};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// How often a SQLite database is checked for changed posts, since it can't notify the backend itself.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to wait before listening again after losing the PostgreSQL connection.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// An event telling the backend's in-memory state that posts changed.
+#[derive(Debug, Clone)]
+pub enum Invalidation {
+    /// The posts with these permalinks were added, updated or removed.
+    Posts(Vec<String>),
+    /// Any post may have changed, such as after missing notifications while disconnected.
+    All,
+}
+
+//=====================================================================================================================
+/// 📣 Publish an invalidation event whenever posts change in the database.
+/// PostgreSQL is notified by `foil build`, while SQLite is polled for changes.
+pub fn watch_posts(pool: &FoilPool) -> broadcast::Sender<Invalidation> {
+    let (sender, _) = broadcast::channel::<Invalidation>(64);
+    let task_sender = sender.clone();
+    match pool.clone() {
+        FoilPool::Postgres(p) => {
+            tokio::spawn(listen_posts(p, task_sender));
+        }
+        FoilPool::Sqlite(p) => {
+            tokio::spawn(poll_posts(p, task_sender));
+        }
+    }
+    sender
+}
+
+//=====================================================================================================================
+/// Listen for the foil CLI's notifications, reconnecting and resubscribing whenever the database restarts.
+async fn listen_posts(pool: Pool<Postgres>, sender: broadcast::Sender<Invalidation>) {
+    loop {
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(
+                    "Failed to connect to database for post notifications: {}",
+                    redact_credentials(&e.to_string())
+                );
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        if let Err(e) = listener.listen(POSTS_CHANNEL).await {
+            error!(
+                "Failed to listen for post notifications: {}",
+                redact_credentials(&e.to_string())
+            );
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            continue;
+        }
+        // Anything could have changed while we weren't listening.
+        let _ = sender.send(Invalidation::All);
+        trace!("📣 Listening for post notifications on {}.", POSTS_CHANNEL);
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    let permalinks = notification
+                        .payload()
+                        .lines()
+                        .map(|p| p.to_string())
+                        .collect();
+                    // Sending only fails if nothing is subscribed, which is fine.
+                    let _ = sender.send(Invalidation::Posts(permalinks));
+                }
+                Ok(None) => {
+                    // The connection was lost and re-established, so notifications may have been missed.
+                    warn!("Lost connection while listening for post notifications, reconnected.");
+                    let _ = sender.send(Invalidation::All);
+                }
+                Err(e) => {
+                    error!(
+                        "Stopped receiving post notifications: {}",
+                        redact_credentials(&e.to_string())
+                    );
+                    break;
+                }
+            }
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+//=====================================================================================================================
+/// The version of the posts table, which every write to a post or its authors bumps.
+async fn query_posts_version(pool: &Pool<Sqlite>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(include_str!("sql/sqlite/posts_version.sql"))
+        .fetch_one(pool)
+        .await
+}
+
+/// Check a SQLite database for changed posts on an interval.
+async fn poll_posts(pool: Pool<Sqlite>, sender: broadcast::Sender<Invalidation>) {
+    let mut version = None;
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let cur_version = match query_posts_version(&pool).await {
+            Ok(v) => Some(v),
+            Err(e) => {
+                error!(
+                    "Failed to check posts for changes: {}",
+                    redact_credentials(&e.to_string())
+                );
+                continue;
+            }
+        };
+        if version.is_some() && cur_version != version {
+            let _ = sender.send(Invalidation::All);
+        }
+        version = cur_version;
+    }
+}
@@ -2,70 +2,68 @@
 #![warn(unused_crate_dependencies)]
 
 mod graphql;
+mod invalidation;
+mod routes;
+mod sitemap;
 
+use async_graphql::futures_util::{stream, Stream};
 use axum::{
     body::Body,
     error_handling::HandleErrorLayer,
     extract::Extension,
     http::{Request, StatusCode},
     middleware::{self, Next},
+    response::sse::{Event, KeepAlive, Sse},
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use axum::{extract::State, http::uri::Uri};
-use glob::Pattern;
 use graphql::{graphql_handler, graphql_playground_handler, graphql_schema};
 use hyper_util::{client::legacy::connect::HttpConnector, rt::TokioExecutor};
+use invalidation::watch_posts;
+use log::trace;
+use percent_encoding::percent_decode_str;
 use regex::Regex;
-use std::{net::SocketAddr, path::PathBuf};
+use routes::{fold_case, load_routes, SharedRoutes};
+use sitemap::{handler_sitemap, handler_sitemap_page, SiteUrl};
+use std::path::PathBuf;
 
+use foil_core::pool::is_sqlite_url;
+use foil_core::{
+    clean_path_string, redact_credentials, redact_db_url, AssetRule, FoilPool, ListenAddress,
+    ServerConfig,
+};
 use sqlx::ConnectOptions;
-use sqlx::{postgres::PgConnectOptions, Pool, Postgres};
+use sqlx::{postgres::PgConnectOptions, sqlite::SqliteConnectOptions, Pool};
 use std::time::Duration;
 use tower::{BoxError, ServiceBuilder, ServiceExt};
 use tower_http::{compression::CompressionLayer, trace::TraceLayer};
 
 use std::borrow::Cow;
+use std::convert::Infallible;
 use std::env;
 use std::str::FromStr;
-
-use lexiclean::Lexiclean;
-use path_slash::PathBufExt;
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+use unicode_normalization::UnicodeNormalization;
 
 type Client = hyper_util::client::legacy::Client<HttpConnector, Body>;
 
-fn clean_path_string(p: &std::path::PathBuf) -> String {
-    p.clone()
-        .lexiclean()
-        .to_slash()
-        .unwrap()
-        .to_string()
-        .replace("\\", "/")
-}
-
 /// State used for the server-side renderer and static asset router.
 #[derive(Debug, Clone)]
 struct RendererState {
     pub client: Client,
-    pub pool: Pool<Postgres>,
+    pub routes: SharedRoutes,
+    /// The `host:port` of the Node.js server-side renderer.
+    pub renderer_address: String,
+    /// How long the renderer may take to answer before the request fails.
+    pub renderer_timeout: Duration,
 }
 
-//=====================================================================================================================
-/// Query a given permalink's recursive post.
-async fn query_post_recursive(
-    pool: &Pool<Postgres>,
-    permalink: &String,
-) -> Result<(String, String, Vec<String>, String), sqlx::Error> {
-    let cur_query = include_str!("graphql/sql/post_recursive.sql");
-    // (Root Path, Permalink, Assets, Main)
-    let sql_result: Result<(String, String, Vec<String>, String), sqlx::Error> =
-        sqlx::query_as(&cur_query)
-            .bind(permalink)
-            .fetch_one(pool)
-            .await;
-    return sql_result;
-}
+/// Decoded request paths with symbols not allowed in file names, or that try to leave the site's folders.
+static INVALID_PATH: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"[\x00-\x1f\x7f<>:"|?*]|(\/\.)|(\/\/+)|(\\+)"#).unwrap());
 
 //=====================================================================================================================
 /// Reverse proxy get requests to Node.js renderer.
@@ -83,10 +81,14 @@ async fn handler_renderer(
         .body("".into())
         .unwrap();
 
-    // Early out any uri path that features non-alphanumeric symbols or symbols not allowed in file names.
-    let path = req.uri().path().trim_ascii().to_ascii_lowercase();
-    let re = Regex::new(r"[^A-Za-z0-9/\.\/\-\@]|(\/\.)|(\/\/+)|(\\+)").unwrap();
-    if re.is_match(&path) {
+    // 🔤 Decode the uri path, so permalinks and files can use accents, spaces or any other Unicode character.
+    // Paths are normalized to NFC, since the same name can be written in several ways.
+    let path: String = match percent_decode_str(req.uri().path()).decode_utf8() {
+        Ok(decoded) => decoded.trim_ascii().nfc().collect(),
+        Err(_) => return Ok(res_bad),
+    };
+    // Early out any uri path that features symbols not allowed in file names.
+    if INVALID_PATH.is_match(&path) {
         return Ok(res_bad);
     }
 
@@ -102,13 +104,15 @@ async fn handler_renderer(
             .map(|v| v.as_str())
             .unwrap_or("/404");
 
-        let uri = format!("http://127.0.0.1:4011{}", path_query);
+        let uri = format!("http://{}{}", state.renderer_address, path_query);
         *req.uri_mut() = Uri::try_from(uri).unwrap();
 
         let client_res = state.client.request(req);
+        let renderer_timeout = state.renderer_timeout;
         return tokio::spawn(async move {
-            let res = client_res
+            let res = tokio::time::timeout(renderer_timeout, client_res)
                 .await
+                .map_err(|_| StatusCode::GATEWAY_TIMEOUT)?
                 .map_err(|_| StatusCode::BAD_REQUEST)?
                 .into_response();
             Ok::<_, StatusCode>(res)
@@ -117,72 +121,95 @@ async fn handler_renderer(
         .unwrap();
     }
 
-    // We try to resolve paths that have an extension using what's exposed from foil modules relative to the path.
-    // We cache queried results to speed this up.
-    let mut path_ancestors = path_pathbuf.ancestors();
-    path_ancestors.next();
-    loop {
-        let ancestor = path_ancestors.next();
-        match ancestor {
-            Some(par) => {
-                let par_path_buf = par.to_path_buf();
-                let par_clean = clean_path_string(&par_path_buf);
-                match query_post_recursive(&state.pool, &par_clean).await {
-                    Ok(v) => {
-                        // 🤍 Early out based on allowed paths and extensions.
-                        // We first check if the foil main is the path, then check our whitelist.
-                        let mut can_serve = v.3 == path;
-                        if !can_serve {
-                            for asset in v.2 {
-                                let full_asset_path_buf = PathBuf::from(&v.1).join(&asset);
-                                let full_asset_path = clean_path_string(&full_asset_path_buf);
-                                match Pattern::new(&full_asset_path) {
-                                    Ok(pat) => {
-                                        can_serve |= pat.matches(&path);
-                                        break;
-                                    }
-                                    Err(_) => (),
-                                }
-                            }
-                        }
-                        if !can_serve {
-                            return Ok(res_not_found);
-                        }
-
-                        // 🫚 Split the permalink from the current request path:
-                        // Example: /blog/ray-tracing-denoising/assets/cover.jpg becomes:
-                        // Result: asset/cover.jpg
-                        let mut cur_path_string = path.to_string().replacen(&v.1, "", 1);
-                        if cur_path_string.starts_with("/") {
-                            cur_path_string = cur_path_string.replacen("/", "", 1);
-                        }
-                        match PathBuf::from_str(&v.0) {
-                            Ok(post_root) => {
-                                let possible_file_path = post_root.join(&cur_path_string);
-                                let svc = tower_http::services::ServeFile::new(possible_file_path);
-                                return tokio::spawn(async move {
-                                    let svc_resp = svc.oneshot(Request::new(Body::empty()));
-                                    let res = svc_resp.await.into_response();
-                                    Ok::<_, StatusCode>(res)
-                                })
-                                .await
-                                .unwrap();
-                            }
-                            Err(_e) => (),
-                        }
-                    }
-                    Err(_sql_e) => (),
+    // We try to resolve paths that have an extension using what's exposed from the closest foil module above the path.
+    // Every post's route is kept in memory, so this is a single lookup.
+    let routes = state.routes.read().unwrap().clone();
+    if let Some((v, relative_path)) = routes.find(&path) {
+        // 🔤 Permalinks are matched regardless of case, so find the file with its exact case on disk.
+        // Example: /blog/ray-tracing-denoising/assets/cover.jpg becomes:
+        // Result: Assets/Cover.JPG
+        let file_path = match v.resolve_file(&relative_path).await {
+            Some(file_path) => file_path,
+            None => return Ok(res_not_found),
+        };
+        let asset_path = clean_path_string(&PathBuf::from(&v.permalink).join(&file_path));
+
+        // 🤍 Early out based on allowed paths and extensions.
+        // We first check if the foil main is the path, then check our whitelist.
+        let mut can_serve = fold_case(&v.main) == fold_case(&path);
+        if !can_serve {
+            match AssetRule::find_match(&v.assets, &asset_path) {
+                Some(rule) if !rule.negated => {
+                    trace!(
+                        "🤍 Serving {}, allowed by {}'s asset rule {}.",
+                        asset_path,
+                        v.permalink,
+                        rule.rule
+                    );
+                    can_serve = true;
                 }
+                Some(rule) => trace!(
+                    "Denied {}, excluded by {}'s asset rule {}.",
+                    asset_path,
+                    v.permalink,
+                    rule.rule
+                ),
+                None => trace!(
+                    "Denied {}, no asset rule of {} matches it.",
+                    asset_path,
+                    v.permalink
+                ),
             }
-            None => {
-                break;
-            }
         }
+        if !can_serve {
+            return Ok(res_not_found);
+        }
+
+        let possible_file_path = PathBuf::from(&v.root_path).join(&file_path);
+        let svc = tower_http::services::ServeFile::new(possible_file_path);
+        return tokio::spawn(async move {
+            let svc_resp = svc.oneshot(Request::new(Body::empty()));
+            let res = svc_resp.await.into_response();
+            Ok::<_, StatusCode>(res)
+        })
+        .await
+        .unwrap();
     }
 
     // We couldn't find a file due to a server error, so we 404 and redirect to the 404 frontend page:
     return Ok(res_not_found);
 }
+//=====================================================================================================================
+/// 🔄 Stream live reload events to the browser while running `foil dev`.
+async fn handler_dev_events(
+    Extension(reload): Extension<broadcast::Sender<String>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = stream::unfold(reload.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(permalinks) => {
+                    let event = Event::default().event("reload").data(permalinks);
+                    return Some((Ok(event), rx));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+//=====================================================================================================================
+/// 📣 Notify every connected browser that the given newline separated permalinks were rebuilt.
+async fn handler_dev_reload(
+    Extension(reload): Extension<broadcast::Sender<String>>,
+    permalinks: String,
+) -> StatusCode {
+    // Sending only fails if no browsers are listening, which is fine.
+    let _ = reload.send(permalinks);
+    StatusCode::NO_CONTENT
+}
+
 //=====================================================================================================================
 /// Error handling for the foil server.
 async fn handle_error(error: BoxError) -> impl IntoResponse {
@@ -235,54 +262,174 @@ async fn add_headers(req: Request<Body>, next: Next) -> Result<Response, Respons
     Ok(response)
 }
 
+//=====================================================================================================================
+/// 🙈 Stop the backend after a database error, without ever printing the database password.
+fn fatal_db_error(message: &str, db_url: &str, e: sqlx::Error) -> ! {
+    panic!(
+        "Fatal Error: {} {}: {}",
+        message,
+        redact_db_url(db_url),
+        redact_credentials(&e.to_string())
+    );
+}
+
 //=====================================================================================================================
 // Backend server main.
 #[tokio::main]
 async fn main() {
+    // 🏷️ The foil CLI checks that the backend it spawns is the same version as itself.
+    if env::args().any(|arg| arg == "--version") {
+        println!("foil_backend {}", env!("CARGO_PKG_VERSION"));
+        return;
+    }
     // 🧻 Start logger
     env_logger::init();
-    // 📚 Configure Database
-    let pg_url = env::var("FOIL_DATABASE_URL")
+    // 📚 Configure Database, picking PostgreSQL or SQLite by the URL's scheme.
+    let db_url = env::var("FOIL_DATABASE_URL")
         .expect("Fatal Error: No environment var FOIL_DATABASE_URL found.");
-    let opts = PgConnectOptions::from_str(&pg_url)
-        .unwrap()
-        .log_statements(log::LevelFilter::Trace);
-    let postgres_pool: Pool<Postgres> = Pool::connect_with(opts)
-        .await
-        .expect("Fatal Error: Cannot connect to database.");
+    let foil_pool = if is_sqlite_url(&db_url) {
+        // SQLite has no regex operator, so register one for permalink globs.
+        let opts = SqliteConnectOptions::from_str(&db_url)
+            .unwrap_or_else(|e| fatal_db_error("Invalid SQLite database URL", &db_url, e))
+            .with_regexp()
+            .log_statements(log::LevelFilter::Trace);
+        FoilPool::Sqlite(
+            Pool::connect_with(opts)
+                .await
+                .unwrap_or_else(|e| fatal_db_error("Cannot open SQLite database", &db_url, e)),
+        )
+    } else {
+        let opts = PgConnectOptions::from_str(&db_url)
+            .unwrap_or_else(|e| fatal_db_error("Invalid database URL", &db_url, e))
+            .log_statements(log::LevelFilter::Trace);
+        FoilPool::Postgres(
+            Pool::connect_with(opts)
+                .await
+                .unwrap_or_else(|e| fatal_db_error("Cannot connect to database", &db_url, e)),
+        )
+    };
+
+    // ⚙️ Load the listen addresses, renderer, timeouts and public base URL from foil.toml and the environment.
+    let config = ServerConfig::load().unwrap_or_else(|e| panic!("Fatal Error: {}", e));
+
+    // 📣 Invalidate in-memory state whenever posts change, subscribed to by the route table and any handler.
+    let invalidations = watch_posts(&foil_pool);
 
     // 🎒 Create Backend Server
     let renderer_state = RendererState {
         client: hyper_util::client::legacy::Client::<(), ()>::builder(TokioExecutor::new())
             .build(HttpConnector::new()),
-        pool: postgres_pool.clone(),
+        routes: load_routes(&foil_pool, invalidations.subscribe())
+            .await
+            .expect("Fatal Error: Cannot load routes from database."),
+        renderer_address: config.renderer_address.clone(),
+        renderer_timeout: config.renderer_timeout,
     };
 
-    let app = Router::new()
+    let mut app: Router<RendererState> = Router::new()
         // 📊 GraphQL
         .route(
             "/api/v1/graphql",
             get(graphql_playground_handler).post(graphql_handler),
         )
+        // 🗺️ Sitemap
+        .route("/sitemap.xml", get(handler_sitemap))
+        .route("/sitemap-{page}", get(handler_sitemap_page));
+
+    // 🔄 Live reload events, only exposed when running `foil dev`.
+    let live_reload = env::var("FOIL_LIVE_RELOAD").is_ok_and(|v| v == "1");
+    let (reload_sender, _) = broadcast::channel::<String>(16);
+    if live_reload {
+        app = app
+            .route("/api/v1/dev/events", get(handler_dev_events))
+            .route("/api/v1/dev/reload", post(handler_dev_reload));
+    }
+
+    let app = app
         // ⚛️ Single Page Application HTML Template
         .fallback(get(handler_renderer))
-        .layer(Extension(graphql_schema(&postgres_pool)))
-        .layer(Extension(postgres_pool.clone()))
+        .layer(Extension(graphql_schema(&foil_pool)))
+        .layer(Extension(foil_pool.clone()))
+        .layer(Extension(reload_sender))
+        .layer(Extension(invalidations))
+        .layer(Extension(SiteUrl(config.site_url())))
         .layer(ServiceBuilder::new().layer(middleware::from_fn(add_headers)))
         .layer(
             ServiceBuilder::new()
                 .layer(HandleErrorLayer::new(handle_error))
-                .timeout(Duration::from_secs(300))
+                .timeout(config.request_timeout)
                 .layer(TraceLayer::new_for_http())
                 .layer(CompressionLayer::new()),
         )
         .with_state(renderer_state);
 
-    // ✨ Bind Foil Backend:
-    println!("✨ Foil Backend Server running in http://localhost:4017");
-    let addr = SocketAddr::from(([127, 0, 0, 1], 4017));
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app.layer(TraceLayer::new_for_http()))
-        .await
-        .unwrap();
+    // ✨ Bind Foil Backend to every listen address:
+    let app = app.layer(TraceLayer::new_for_http());
+    let mut servers = vec![];
+    for address in config.listen.iter() {
+        servers.push(tokio::spawn(serve(address.clone(), app.clone())));
+    }
+    for server in servers {
+        server.await.unwrap();
+    }
+}
+
+//=====================================================================================================================
+/// 🛑 Wait for SIGINT or SIGTERM, such as from `foil server start` shutting down, to stop accepting requests.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => (),
+        _ = terminate => (),
+    }
+}
+
+//=====================================================================================================================
+/// 👂 Serve the foil backend on a TCP or Unix socket.
+async fn serve(address: ListenAddress, app: Router) {
+    match address {
+        ListenAddress::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .unwrap_or_else(|e| panic!("Fatal Error: Cannot listen on {}: {}", addr, e));
+            println!("✨ Foil Backend Server running in http://{}", addr);
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .unwrap();
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            // A socket left behind by a previous run would make binding fail.
+            let _ = std::fs::remove_file(&path);
+            let listener = tokio::net::UnixListener::bind(&path).unwrap_or_else(|e| {
+                panic!("Fatal Error: Cannot listen on {}: {}", path.display(), e)
+            });
+            println!("✨ Foil Backend Server running in unix:{}", path.display());
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .unwrap();
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(path) => {
+            panic!(
+                "Fatal Error: Cannot listen on unix:{}, Unix sockets aren't supported on this platform.",
+                path.display()
+            );
+        }
+    }
 }
@@ -0,0 +1,175 @@
+use crate::invalidation::Invalidation;
+use foil_core::{clean_path_string, match_pool, redact_credentials, AssetRule, FoilPool, SQLPost};
+use log::{error, trace, warn};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+use unicode_normalization::UnicodeNormalization;
+
+/// Fold a permalink or path segment so it can be compared regardless of case or Unicode normalization form.
+pub fn fold_case(s: &str) -> String {
+    s.nfc().collect::<String>().to_lowercase()
+}
+
+/// What a post exposes to the static asset router.
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// The folder this post's `package.json` is in, where its assets are served from.
+    pub root_path: String,
+    pub permalink: String,
+    /// Rules for the public assets served from this post's folder, compiled from its `assets` globs.
+    pub assets: Vec<AssetRule>,
+    /// The post's compiled main javascript file.
+    pub main: String,
+}
+
+impl Route {
+    /// 🔤 Resolve a path relative to this post to the file on disk, keeping the file's exact case.
+    /// Each segment is matched exactly first, then regardless of case or Unicode normalization form.
+    pub async fn resolve_file(&self, relative_path: &str) -> Option<String> {
+        let mut dir = PathBuf::from(&self.root_path);
+        let mut resolved: Vec<String> = vec![];
+        for segment in relative_path.split('/').filter(|s| !s.is_empty()) {
+            let name = if tokio::fs::metadata(dir.join(segment)).await.is_ok() {
+                segment.to_string()
+            } else {
+                let folded = fold_case(segment);
+                let mut entries = tokio::fs::read_dir(&dir).await.ok()?;
+                let mut found = None;
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    if let Some(entry_name) = entry.file_name().to_str() {
+                        if fold_case(entry_name) == folded {
+                            found = Some(entry_name.to_string());
+                            break;
+                        }
+                    }
+                }
+                found?
+            };
+            dir.push(&name);
+            resolved.push(name);
+        }
+        Some(resolved.join("/"))
+    }
+}
+
+/// A node of the route table, one per permalink segment.
+#[derive(Debug, Default)]
+struct RouteNode {
+    route: Option<Route>,
+    children: HashMap<String, RouteNode>,
+}
+
+//=====================================================================================================================
+/// 🌳 Every post's route, in a prefix tree keyed by case folded permalink segments.
+#[derive(Debug, Default)]
+pub struct RouteTable {
+    root: RouteNode,
+}
+
+impl RouteTable {
+    fn from_posts(posts: Vec<SQLPost>) -> RouteTable {
+        let mut table = RouteTable::default();
+        for post in posts {
+            let permalink = fold_case(&clean_path_string(&PathBuf::from(&post.permalink)));
+            let assets = post
+                .assets
+                .iter()
+                .filter_map(|rule| match AssetRule::new(&post.permalink, rule) {
+                    Ok(asset_rule) => Some(asset_rule),
+                    Err(e) => {
+                        warn!(
+                            "Ignoring invalid asset rule {} of {}: {}",
+                            rule, post.permalink, e
+                        );
+                        None
+                    }
+                })
+                .collect();
+            let mut node = &mut table.root;
+            for segment in permalink.split('/').filter(|s| !s.is_empty()) {
+                node = node.children.entry(segment.to_string()).or_default();
+            }
+            node.route = Some(Route {
+                root_path: post.root_path,
+                permalink: post.permalink,
+                assets,
+                main: post.main,
+            });
+        }
+        table
+    }
+
+    /// Find the route of the closest post above a file's path, such as `/blog/post` for `/blog/post/assets/cover.jpg`,
+    /// along with the file's path relative to that post.
+    pub fn find(&self, path: &str) -> Option<(&Route, String)> {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut node = &self.root;
+        let mut found = node.route.as_ref().map(|route| (route, 0));
+        // The file itself is never a post.
+        for (i, segment) in segments
+            .iter()
+            .take(segments.len().saturating_sub(1))
+            .enumerate()
+        {
+            match node.children.get(&fold_case(segment)) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(route) = node.route.as_ref() {
+                found = Some((route, i + 1));
+            }
+        }
+        found.map(|(route, depth)| (route, segments[depth..].join("/")))
+    }
+}
+
+/// The route table shared between requests, swapped out whole when posts change.
+pub type SharedRoutes = Arc<RwLock<Arc<RouteTable>>>;
+
+//=====================================================================================================================
+/// Load every post's route from the database.
+async fn query_route_table(pool: &FoilPool) -> Result<RouteTable, sqlx::Error> {
+    let posts: Vec<SQLPost> = match_pool!(pool, p => {
+        sqlx::query_as(include_str!("sql/posts_routes.sql"))
+            .fetch_all(p)
+            .await
+    })?;
+    Ok(RouteTable::from_posts(posts))
+}
+
+//=====================================================================================================================
+/// 🌳 Load the route table, and reload it whenever posts are invalidated.
+pub async fn load_routes(
+    pool: &FoilPool,
+    mut invalidations: broadcast::Receiver<Invalidation>,
+) -> Result<SharedRoutes, sqlx::Error> {
+    let routes: SharedRoutes = Arc::new(RwLock::new(Arc::new(query_route_table(pool).await?)));
+
+    let pool = pool.clone();
+    let shared_routes = routes.clone();
+    tokio::spawn(async move {
+        loop {
+            match invalidations.recv().await {
+                Ok(Invalidation::Posts(permalinks)) => {
+                    trace!("🌳 Posts changed: {}", permalinks.join(", "))
+                }
+                // Lagging behind only means several reloads were asked for, one covers them all.
+                Ok(Invalidation::All) | Err(broadcast::error::RecvError::Lagged(_)) => (),
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+            match query_route_table(&pool).await {
+                Ok(table) => {
+                    trace!("🌳 Reloaded route table.");
+                    *shared_routes.write().unwrap() = Arc::new(table);
+                }
+                Err(e) => error!(
+                    "Failed to reload route table: {}",
+                    redact_credentials(&e.to_string())
+                ),
+            }
+        }
+    });
+    Ok(routes)
+}
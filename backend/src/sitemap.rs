@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Extension, Path},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use foil_core::sitemap::{
+    query_sitemap_posts, sitemap_page, write_sitemap_index, write_urlset, SitemapPost,
+    SITEMAP_MAX_URLS,
+};
+use foil_core::FoilPool;
+
+/// The public base URL of the site, without a trailing slash.
+#[derive(Debug, Clone)]
+pub struct SiteUrl(pub String);
+
+fn xml_response(body: String) -> Response {
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        body,
+    )
+        .into_response()
+}
+
+//=====================================================================================================================
+/// 🗺️ Serve the sitemap of every published post, or a sitemap index once there's more than 50,000 of them.
+pub async fn handler_sitemap(
+    Extension(pool): Extension<FoilPool>,
+    Extension(site_url): Extension<SiteUrl>,
+) -> Result<Response, StatusCode> {
+    let posts = query_sitemap_posts(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if posts.len() <= SITEMAP_MAX_URLS {
+        return Ok(xml_response(write_urlset(&site_url.0, &posts)));
+    }
+    let pages: Vec<&[SitemapPost]> = posts.chunks(SITEMAP_MAX_URLS).collect();
+    Ok(xml_response(write_sitemap_index(&site_url.0, &pages)))
+}
+
+//=====================================================================================================================
+/// 🗺️ Serve a page of a split sitemap, such as `/sitemap-2.xml`.
+pub async fn handler_sitemap_page(
+    Extension(pool): Extension<FoilPool>,
+    Extension(site_url): Extension<SiteUrl>,
+    Path(page): Path<String>,
+) -> Result<Response, StatusCode> {
+    let page: usize = page
+        .strip_suffix(".xml")
+        .and_then(|p| p.parse().ok())
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let posts = query_sitemap_posts(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Pages only exist once the sitemap is split.
+    match sitemap_page(&posts, page) {
+        Some(page_posts) => Ok(xml_response(write_urlset(&site_url.0, page_posts))),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
@@ -0,0 +1 @@
+SELECT value FROM foil_meta WHERE key = 'posts_version'
\ No newline at end of file
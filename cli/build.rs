@@ -9,7 +9,7 @@ fn main() {
 
     // 🌳 Current branch:
     let cur_branch = Command::new("git")
-        .args(&["rev-parse", "--abbrev-ref", "HEAD"])
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
         .output()
         .unwrap()
         .stdout;
@@ -18,7 +18,7 @@ fn main() {
 
     // 🍃 Current commit:
     let cur_commit = Command::new("git")
-        .args(&["rev-parse", "--short", "HEAD"])
+        .args(["rev-parse", "--short", "HEAD"])
         .output()
         .unwrap()
         .stdout;
@@ -1,5 +1,3 @@
 DROP TABLE IF EXISTS posts;
 
 DROP TYPE IF EXISTS author CASCADE;
-
-DROP TYPE IF EXISTS redirect CASCADE;
\ No newline at end of file
@@ -1,8 +1,15 @@
-CREATE TYPE author AS (
-    name varchar(254),
-    email varchar(254),
-    url varchar(254)
-);
+-- Databases created before foil had versioned migrations already have these, so they're only created if missing.
+DO $$
+BEGIN
+    IF to_regtype('author') IS NULL THEN
+        CREATE TYPE author AS (
+            name varchar(254),
+            email varchar(254),
+            url varchar(254)
+        );
+    END IF;
+END
+$$;
 
 CREATE TABLE IF NOT EXISTS posts (
     id serial PRIMARY KEY NOT NULL,
@@ -21,7 +28,9 @@ CREATE TABLE IF NOT EXISTS posts (
     root_path varchar(254) NOT NULL UNIQUE,
     public_modules varchar(254)[] NOT NULL,
     rss varchar(254)[] NOT NULL,
-    assets varchar(254)[] NOT NULL
+    assets varchar(254)[] NOT NULL,
+    feeds varchar(254)[] NOT NULL
 );
 
-SET CLIENT_ENCODING TO 'utf8';
\ No newline at end of file
+-- Those databases predate feeds.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS feeds varchar(254)[] NOT NULL DEFAULT '{}';
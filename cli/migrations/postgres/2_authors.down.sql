@@ -0,0 +1,20 @@
+DROP VIEW IF EXISTS posts_with_authors;
+
+CREATE TYPE author AS (
+    name varchar(254),
+    email varchar(254),
+    url varchar(254)
+);
+
+-- Copy authors back into each post, losing their avatars, bios and social links.
+ALTER TABLE posts ADD COLUMN authors author[] NOT NULL DEFAULT '{}';
+UPDATE posts SET authors = COALESCE((
+    SELECT array_agg(ROW(authors.name, authors.email, authors.url)::author ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+), '{}');
+ALTER TABLE posts ALTER COLUMN authors DROP DEFAULT;
+
+DROP TABLE IF EXISTS post_authors;
+DROP TABLE IF EXISTS authors;
@@ -0,0 +1,52 @@
+-- Authors are shared by every post that lists them, instead of being copied into each post.
+CREATE TABLE IF NOT EXISTS authors (
+    id serial PRIMARY KEY NOT NULL,
+    name varchar(254) NOT NULL UNIQUE,
+    email varchar(254) NOT NULL DEFAULT '',
+    url varchar(254) NOT NULL DEFAULT '',
+    avatar varchar(254),
+    bio text,
+    socials jsonb NOT NULL DEFAULT '[]'
+);
+
+-- The authors of each post, in the order its package.json lists them.
+CREATE TABLE IF NOT EXISTS post_authors (
+    post_id integer NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    author_id integer NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    position integer NOT NULL,
+    PRIMARY KEY (post_id, author_id)
+);
+CREATE INDEX IF NOT EXISTS post_authors_author_id ON post_authors (author_id);
+
+-- Move the authors of existing posts into the new tables, keeping the most recently modified post's details.
+INSERT INTO authors (name, email, url)
+SELECT DISTINCT ON (a.name) a.name, COALESCE(a.email, ''), COALESCE(a.url, '')
+FROM posts, unnest(posts.authors) AS a
+WHERE a.name IS NOT NULL
+ORDER BY a.name, posts.date_modified DESC;
+
+INSERT INTO post_authors (post_id, author_id, position)
+SELECT posts.id, authors.id, MIN(a.position)
+FROM posts, unnest(posts.authors) WITH ORDINALITY AS a(name, email, url, position)
+JOIN authors ON authors.name = a.name
+GROUP BY posts.id, authors.id;
+
+ALTER TABLE posts DROP COLUMN authors;
+DROP TYPE IF EXISTS author;
+
+-- Posts along with their authors as a JSON array, which is what foil reads posts from.
+CREATE VIEW posts_with_authors AS
+SELECT posts.*, COALESCE((
+    SELECT json_agg(json_build_object(
+        'name', authors.name,
+        'email', authors.email,
+        'url', authors.url,
+        'avatar', authors.avatar,
+        'bio', authors.bio,
+        'socials', authors.socials
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+), '[]'::json) AS authors
+FROM posts;
@@ -0,0 +1,19 @@
+DROP VIEW IF EXISTS posts_with_authors;
+CREATE VIEW posts_with_authors AS
+SELECT posts.*, COALESCE((
+    SELECT json_agg(json_build_object(
+        'name', authors.name,
+        'email', authors.email,
+        'url', authors.url,
+        'avatar', authors.avatar,
+        'bio', authors.bio,
+        'socials', authors.socials
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+), '[]'::json) AS authors
+FROM posts;
+
+DROP INDEX IF EXISTS authors_slug;
+ALTER TABLE authors DROP COLUMN slug;
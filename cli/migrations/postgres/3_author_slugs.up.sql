@@ -0,0 +1,30 @@
+-- Author pages are found by a slug derived from each author's name when their posts are built.
+ALTER TABLE authors ADD COLUMN slug varchar(254) NOT NULL DEFAULT '';
+
+-- Existing authors get their slug from their name the same way their next build does:
+-- lowercase ASCII letters and numbers, with every run of other characters turned into a dash.
+UPDATE authors SET slug = lower(trim(BOTH '-' FROM regexp_replace(name, '[^A-Za-z0-9]+', '-', 'g')));
+UPDATE authors SET slug = 'author-' || id WHERE slug = '';
+UPDATE authors SET slug = slug || '-' || id
+WHERE id NOT IN (SELECT MIN(id) FROM authors GROUP BY slug);
+
+ALTER TABLE authors ALTER COLUMN slug DROP DEFAULT;
+CREATE UNIQUE INDEX IF NOT EXISTS authors_slug ON authors (slug);
+
+DROP VIEW IF EXISTS posts_with_authors;
+CREATE VIEW posts_with_authors AS
+SELECT posts.*, COALESCE((
+    SELECT json_agg(json_build_object(
+        'name', authors.name,
+        'slug', authors.slug,
+        'email', authors.email,
+        'url', authors.url,
+        'avatar', authors.avatar,
+        'bio', authors.bio,
+        'socials', authors.socials
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+), '[]'::json) AS authors
+FROM posts;
@@ -0,0 +1,21 @@
+-- SQLite has no arrays or composite types, so authors and lists are stored as JSON arrays.
+CREATE TABLE IF NOT EXISTS posts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    permalink TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    authors TEXT NOT NULL,
+    description TEXT NOT NULL,
+    keywords TEXT NOT NULL,
+    covers TEXT NOT NULL,
+    main TEXT NOT NULL,
+    date_published TEXT NOT NULL,
+    date_modified TEXT NOT NULL,
+
+    output_path TEXT NOT NULL,
+    root_path TEXT NOT NULL UNIQUE,
+    public_modules TEXT NOT NULL,
+    rss TEXT NOT NULL,
+    assets TEXT NOT NULL,
+    feeds TEXT NOT NULL
+);
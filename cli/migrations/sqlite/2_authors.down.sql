@@ -0,0 +1,17 @@
+DROP VIEW IF EXISTS posts_with_authors;
+
+-- Copy authors back into each post, losing their avatars, bios and social links.
+ALTER TABLE posts ADD COLUMN authors TEXT NOT NULL DEFAULT '[]';
+UPDATE posts SET authors = (
+    SELECT json_group_array(json_object(
+        'name', authors.name,
+        'email', authors.email,
+        'url', authors.url
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+);
+
+DROP TABLE IF EXISTS post_authors;
+DROP TABLE IF EXISTS authors;
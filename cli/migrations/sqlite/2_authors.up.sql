@@ -0,0 +1,53 @@
+-- Authors are shared by every post that lists them, instead of being copied into each post.
+CREATE TABLE IF NOT EXISTS authors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL UNIQUE,
+    email TEXT NOT NULL DEFAULT '',
+    url TEXT NOT NULL DEFAULT '',
+    avatar TEXT,
+    bio TEXT,
+    socials TEXT NOT NULL DEFAULT '[]'
+);
+
+-- The authors of each post, in the order its package.json lists them.
+CREATE TABLE IF NOT EXISTS post_authors (
+    post_id INTEGER NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    author_id INTEGER NOT NULL REFERENCES authors (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (post_id, author_id)
+);
+CREATE INDEX IF NOT EXISTS post_authors_author_id ON post_authors (author_id);
+
+-- Move the authors of existing posts into the new tables, keeping the most recently modified post's details.
+INSERT OR IGNORE INTO authors (name, email, url)
+SELECT json_extract(a.value, '$.name'),
+    COALESCE(json_extract(a.value, '$.email'), ''),
+    COALESCE(json_extract(a.value, '$.url'), '')
+FROM posts, json_each(posts.authors) AS a
+WHERE json_extract(a.value, '$.name') IS NOT NULL
+ORDER BY posts.date_modified DESC;
+
+INSERT OR IGNORE INTO post_authors (post_id, author_id, position)
+SELECT posts.id, authors.id, a.key
+FROM posts, json_each(posts.authors) AS a
+JOIN authors ON authors.name = json_extract(a.value, '$.name')
+ORDER BY a.key;
+
+ALTER TABLE posts DROP COLUMN authors;
+
+-- Posts along with their authors as a JSON array, which is what foil reads posts from.
+CREATE VIEW posts_with_authors AS
+SELECT posts.*, (
+    SELECT json_group_array(json_object(
+        'name', authors.name,
+        'email', authors.email,
+        'url', authors.url,
+        'avatar', authors.avatar,
+        'bio', authors.bio,
+        'socials', json(authors.socials)
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+) AS authors
+FROM posts;
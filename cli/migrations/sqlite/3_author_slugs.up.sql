@@ -0,0 +1,42 @@
+-- Author pages are found by a slug derived from each author's name when their posts are built.
+ALTER TABLE authors ADD COLUMN slug TEXT NOT NULL DEFAULT '';
+
+-- Existing authors get their slug from their name the same way their next build does:
+-- lowercase ASCII letters and numbers, with every run of other characters turned into a dash.
+-- SQLite has no regular expressions, so each name is walked one character at a time.
+WITH RECURSIVE walk(id, rest, slug) AS (
+    SELECT id, lower(name), '' FROM authors
+    UNION ALL
+    SELECT id, substr(rest, 2), CASE
+        WHEN substr(rest, 1, 1) BETWEEN 'a' AND 'z' OR substr(rest, 1, 1) BETWEEN '0' AND '9'
+            THEN slug || substr(rest, 1, 1)
+        WHEN slug = '' OR substr(slug, -1) = '-' THEN slug
+        ELSE slug || '-'
+    END
+    FROM walk
+    WHERE rest <> ''
+)
+UPDATE authors SET slug = (SELECT trim(walk.slug, '-') FROM walk WHERE walk.id = authors.id AND walk.rest = '');
+UPDATE authors SET slug = 'author-' || id WHERE slug = '';
+UPDATE authors SET slug = slug || '-' || id
+WHERE id NOT IN (SELECT MIN(id) FROM authors GROUP BY slug);
+
+CREATE UNIQUE INDEX IF NOT EXISTS authors_slug ON authors (slug);
+
+DROP VIEW IF EXISTS posts_with_authors;
+CREATE VIEW posts_with_authors AS
+SELECT posts.*, (
+    SELECT json_group_array(json_object(
+        'name', authors.name,
+        'slug', authors.slug,
+        'email', authors.email,
+        'url', authors.url,
+        'avatar', authors.avatar,
+        'bio', authors.bio,
+        'socials', json(authors.socials)
+    ) ORDER BY post_authors.position)
+    FROM post_authors
+    JOIN authors ON authors.id = post_authors.author_id
+    WHERE post_authors.post_id = posts.id
+) AS authors
+FROM posts;
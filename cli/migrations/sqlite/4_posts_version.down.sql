@@ -0,0 +1,11 @@
+DROP TRIGGER IF EXISTS posts_version_insert;
+DROP TRIGGER IF EXISTS posts_version_update;
+DROP TRIGGER IF EXISTS posts_version_delete;
+DROP TRIGGER IF EXISTS post_authors_version_insert;
+DROP TRIGGER IF EXISTS post_authors_version_update;
+DROP TRIGGER IF EXISTS post_authors_version_delete;
+DROP TRIGGER IF EXISTS authors_version_insert;
+DROP TRIGGER IF EXISTS authors_version_update;
+DROP TRIGGER IF EXISTS authors_version_delete;
+
+DROP TABLE IF EXISTS foil_meta;
@@ -0,0 +1,51 @@
+-- SQLite can't notify the backend of changed posts, so every write to a post or its authors bumps a version it polls instead.
+CREATE TABLE IF NOT EXISTS foil_meta (
+    key TEXT PRIMARY KEY NOT NULL,
+    value INTEGER NOT NULL
+);
+INSERT OR IGNORE INTO foil_meta (key, value) VALUES ('posts_version', 0);
+
+CREATE TRIGGER IF NOT EXISTS posts_version_insert AFTER INSERT ON posts
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_version_update AFTER UPDATE ON posts
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS posts_version_delete AFTER DELETE ON posts
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS post_authors_version_insert AFTER INSERT ON post_authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS post_authors_version_update AFTER UPDATE ON post_authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS post_authors_version_delete AFTER DELETE ON post_authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS authors_version_insert AFTER INSERT ON authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS authors_version_update AFTER UPDATE ON authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
+
+CREATE TRIGGER IF NOT EXISTS authors_version_delete AFTER DELETE ON authors
+BEGIN
+    UPDATE foil_meta SET value = value + 1 WHERE key = 'posts_version';
+END;
@@ -0,0 +1,108 @@
+use super::feeds::{Feed, FeedFormat};
+use atom_syndication::{
+    Category, CategoryBuilder, EntryBuilder, FeedBuilder, Link, LinkBuilder, Person, PersonBuilder,
+};
+use foil_core::Author;
+
+fn atom_person(feed: &Feed, author: &Author) -> Person {
+    PersonBuilder::default()
+        .name(author.name.clone())
+        .email(Some(author.email.clone()).filter(|e| !e.is_empty()))
+        .uri(
+            Some(author.url.clone())
+                .filter(|u| !u.is_empty())
+                .map(|u| feed.absolute_url(&u)),
+        )
+        .build()
+}
+
+fn atom_categories(keywords: &[String]) -> Vec<Category> {
+    keywords
+        .iter()
+        .map(|tag| CategoryBuilder::default().term(tag.clone()).build())
+        .collect()
+}
+
+//=====================================================================================================================
+/// Write a feed as an Atom 1.0 document, linking to itself and its alternate formats.
+pub fn write_atom(feed: &Feed) -> String {
+    let root_post = &feed.post;
+
+    // 🔗 Link to this feed's post, itself and its alternate formats:
+    let mut links: Vec<Link> = vec![LinkBuilder::default()
+        .href(feed.absolute_url(&root_post.permalink))
+        .rel("alternate")
+        .mime_type(Some("text/html".to_string()))
+        .build()];
+    for (format, href) in feed.links.iter() {
+        let link = LinkBuilder::default()
+            .href(href.clone())
+            .rel(if *format == FeedFormat::Atom {
+                "self"
+            } else {
+                "alternate"
+            })
+            .mime_type(Some(format.mime_type().to_string()))
+            .build();
+        links.push(link);
+    }
+
+    // 🥬 Build Atom entries:
+    let mut entries = vec![];
+    for found_item in feed.items.iter() {
+        let link = feed.absolute_url(&found_item.permalink);
+        let mut entry_links = vec![LinkBuilder::default()
+            .href(link.clone())
+            .rel("alternate")
+            .build()];
+        if let Some(enclosure) = feed.cover_enclosure(found_item) {
+            let link = LinkBuilder::default()
+                .href(enclosure.url)
+                .rel("enclosure")
+                .mime_type(Some(enclosure.mime_type))
+                .length(Some(enclosure.length.to_string()))
+                .build();
+            entry_links.push(link);
+        }
+        let entry = EntryBuilder::default()
+            .id(link)
+            .title(found_item.title.clone())
+            .summary(Some(found_item.description.clone().into()))
+            .authors(
+                feed.authors_of(found_item)
+                    .iter()
+                    .map(|author| atom_person(feed, author))
+                    .collect::<Vec<Person>>(),
+            )
+            .categories(atom_categories(&found_item.keywords))
+            .links(entry_links)
+            .published(Some(found_item.date_published.fixed_offset()))
+            .updated(found_item.date_modified.fixed_offset())
+            .build();
+        entries.push(entry);
+    }
+
+    // 🌳 Define our application Atom feed:
+    let atom_feed = FeedBuilder::default()
+        .id(feed.link(FeedFormat::Atom))
+        .title(root_post.title.clone())
+        .subtitle(Some(root_post.description.clone().into()))
+        .authors(
+            feed.authors_of(root_post)
+                .iter()
+                .map(|author| atom_person(feed, author))
+                .collect::<Vec<Person>>(),
+        )
+        .categories(atom_categories(&root_post.keywords))
+        .logo(
+            root_post
+                .covers
+                .first()
+                .map(|cover| feed.absolute_url(cover)),
+        )
+        .links(links)
+        .updated(feed.date_modified.fixed_offset())
+        .entries(entries)
+        .build();
+    atom_feed.to_string()
+}
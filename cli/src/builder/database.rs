@@ -1,21 +1,21 @@
 use super::package_schema::NodeAuthor;
 use super::read_foil_package;
 use super::resolver::Foil;
-use crate::error::Result;
+use crate::error::{err, Result};
 use crate::return_err;
 use async_std::task::{spawn, JoinHandle};
 use chrono::{DateTime, Utc};
-use futures::{future, StreamExt, TryStreamExt};
+use foil_core::pool::POSTS_CHANNEL;
+use foil_core::{match_pool, FoilPool};
 use lexiclean::Lexiclean;
 use path_slash::PathBufExt;
-use sqlx::Row;
-use sqlx::{postgres::PgRow, Pool, Postgres};
+use sqlx::types::Json;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 //=====================================================================================================================
 /// Update the database with a given foil post.
-pub async fn udpate_foil_db(foil: Foil, pool: Pool<Postgres>) -> JoinHandle<Result<()>> {
+pub async fn udpate_foil_db(foil: Foil, pool: FoilPool) -> JoinHandle<Result<()>> {
     spawn(async move {
         let root_path_str = foil
             .root_path
@@ -37,134 +37,263 @@ pub async fn udpate_foil_db(foil: Foil, pool: Pool<Postgres>) -> JoinHandle<Resu
         // Define new datetimez:
         let dt = Utc::now();
         let naive_utc = dt.naive_utc();
-        let offset = dt.offset().clone();
+        let offset = *dt.offset();
         let dt_new = DateTime::<Utc>::from_naive_utc_and_offset(naive_utc, offset);
 
-        let found: (i32, DateTime<Utc>) =
+        let found: (i32, DateTime<Utc>) = match_pool!(&pool, p => {
             sqlx::query_as("SELECT id, date_modified FROM posts WHERE permalink = $1")
                 .bind(&foil.permalink)
-                .fetch_one(&pool)
+                .fetch_one(p)
                 .await
-                .unwrap_or((-1, dt_new));
+                .unwrap_or((-1, dt_new))
+        });
 
         let post_id: i32 = found.0;
         let updating = post_id > 0;
 
-        let authors_str = authors_as_sql(&foil.authors);
-        let query = if !updating {
-            format!(
-                r#"
-        INSERT INTO posts 
-        (name, permalink, title, authors, description,
-         keywords, covers, main, date_published,
-         date_modified, output_path, root_path, public_modules,
-         rss, assets) 
-        VALUES ($1, $2, $3, ARRAY[{}]::author[], $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)"#,
-                &authors_str
-            )
-        } else {
-            format!(
-                r#"UPDATE posts SET
-        name = $1, title = $3, authors = ARRAY[{}]::author[], description = $4, 
-        keywords = $5, covers = $6, main = $7, date_published = $8, 
-        date_modified = $9, output_path = $10, root_path = $11, public_modules = $12, 
-        rss = $13, assets = $14
-        WHERE permalink = $2"#,
-                &authors_str
-            )
-        };
-
         let resolved_main = foil.resolve_js_main();
-        let res = sqlx::query(&query)
-            .bind(&foil.name)
-            .bind(&foil.permalink)
-            .bind(&foil.title)
-            .bind(&foil.description)
-            .bind(&foil.keywords)
-            .bind(&foil.covers)
-            .bind(&resolved_main)
-            .bind(&foil.date_published)
-            .bind(&foil.date_modified)
-            // Metadata
-            .bind(&output_path_str)
-            .bind(&root_path_str)
-            .bind(&foil.public_modules)
-            .bind(&foil.rss)
-            .bind(&foil.assets)
-            .execute(&pool)
-            .await;
-        return_err!(res, "Failed to insert foil post to database.");
+        let query = post_query(updating);
+        let res: std::result::Result<i32, sqlx::Error> = match &pool {
+            FoilPool::Postgres(p) => {
+                sqlx::query_scalar(query)
+                    .bind(&foil.name)
+                    .bind(&foil.permalink)
+                    .bind(&foil.title)
+                    .bind(&foil.description)
+                    .bind(&foil.keywords)
+                    .bind(&foil.covers)
+                    .bind(&resolved_main)
+                    .bind(foil.date_published)
+                    .bind(foil.date_modified)
+                    // Metadata
+                    .bind(&output_path_str)
+                    .bind(&root_path_str)
+                    .bind(&foil.public_modules)
+                    .bind(&foil.rss)
+                    .bind(&foil.assets)
+                    .bind(&foil.feeds)
+                    .fetch_one(p)
+                    .await
+            }
+            // SQLite has no arrays, so lists are stored as JSON.
+            FoilPool::Sqlite(p) => {
+                sqlx::query_scalar(query)
+                    .bind(&foil.name)
+                    .bind(&foil.permalink)
+                    .bind(&foil.title)
+                    .bind(&foil.description)
+                    .bind(Json(&foil.keywords))
+                    .bind(Json(&foil.covers))
+                    .bind(&resolved_main)
+                    .bind(foil.date_published)
+                    .bind(foil.date_modified)
+                    // Metadata
+                    .bind(&output_path_str)
+                    .bind(&root_path_str)
+                    .bind(Json(&foil.public_modules))
+                    .bind(Json(&foil.rss))
+                    .bind(Json(&foil.assets))
+                    .bind(Json(&foil.feeds))
+                    .fetch_one(p)
+                    .await
+            }
+        };
+        let post_id = return_err!(res, "Failed to insert foil post to database.");
+        update_post_authors(&pool, post_id, &foil.authors).await?;
+        notify_posts_changed(&pool, std::slice::from_ref(&foil.permalink)).await;
 
         Ok(())
     })
 }
 
 //=====================================================================================================================
-/// Write a node author structure as SQL.
-fn authors_as_sql(authors: &Vec<NodeAuthor>) -> String {
-    let mut out_str = "".to_string();
-    for (i, author) in authors.iter().enumerate() {
-        out_str += &format!(
-            "('{}', '{}', '{}')",
-            &author.name, &author.email, &author.url
-        );
-        if i != authors.len() - 1 {
-            out_str += ","
+/// 📣 Tell any running Foil backend that the given permalinks changed.
+/// SQLite has no notifications, so backends using it find changes on their own.
+async fn notify_posts_changed(pool: &FoilPool, permalinks: &[String]) {
+    let FoilPool::Postgres(p) = pool else {
+        return;
+    };
+    // Notification payloads must be shorter than 8000 bytes, so permalinks are split across several.
+    let mut payloads: Vec<String> = vec![];
+    for permalink in permalinks {
+        match payloads.last_mut() {
+            Some(payload) if payload.len() + permalink.len() + 1 < 8000 => {
+                *payload += "\n";
+                *payload += permalink;
+            }
+            _ => payloads.push(permalink.clone()),
+        }
+    }
+    for payload in payloads {
+        let res = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(POSTS_CHANNEL)
+            .bind(&payload)
+            .execute(p)
+            .await;
+        if res.is_err() {
+            println!("❌ Failed to notify the Foil backend of changed posts.");
         }
     }
-    return out_str;
+}
+
+//=====================================================================================================================
+/// The query to insert or update a foil post, returning its ID.
+fn post_query(updating: bool) -> &'static str {
+    if !updating {
+        r#"
+        INSERT INTO posts
+        (name, permalink, title, description,
+         keywords, covers, main, date_published,
+         date_modified, output_path, root_path, public_modules,
+         rss, assets, feeds)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        RETURNING id"#
+    } else {
+        r#"UPDATE posts SET
+        name = $1, title = $3, description = $4,
+        keywords = $5, covers = $6, main = $7, date_published = $8,
+        date_modified = $9, output_path = $10, root_path = $11, public_modules = $12,
+        rss = $13, assets = $14, feeds = $15
+        WHERE permalink = $2
+        RETURNING id"#
+    }
+}
+
+/// The query to insert an author or update their details, returning their ID.
+/// Authors are shared across posts by name, so details a post leaves out (bound as `NULL`) are kept from other posts,
+/// empty ones clear them, and their slug always follows the latest build.
+const AUTHOR_QUERY: &str = r#"
+    INSERT INTO authors (name, slug, email, url, avatar, bio, socials)
+    VALUES ($1, $2, COALESCE($3, ''), COALESCE($4, ''), NULLIF($5, ''), NULLIF($6, ''), COALESCE($7, '[]'))
+    ON CONFLICT (name) DO UPDATE SET
+    slug = excluded.slug,
+    email = COALESCE($3, authors.email),
+    url = COALESCE($4, authors.url),
+    avatar = CASE WHEN $5 IS NULL THEN authors.avatar ELSE excluded.avatar END,
+    bio = CASE WHEN $6 IS NULL THEN authors.bio ELSE excluded.bio END,
+    socials = COALESCE($7, authors.socials)
+    RETURNING id"#;
+
+/// Authors no longer listed by any post.
+const DELETE_ORPHAN_AUTHORS: &str =
+    "DELETE FROM authors WHERE id NOT IN (SELECT author_id FROM post_authors)";
+
+/// Move an author no post lists anymore out of the way of a slug, such as when an author is renamed.
+/// They're only deleted once every post is written, since another post may still be linking them.
+const FREE_ORPHAN_SLUG: &str = r#"
+    UPDATE authors SET slug = slug || '-' || id
+    WHERE slug = $1 AND name <> $2 AND id NOT IN (SELECT author_id FROM post_authors)"#;
+
+//=====================================================================================================================
+/// 🎇 Link a post to its authors in order, adding any new authors.
+async fn update_post_authors(pool: &FoilPool, post_id: i32, authors: &[NodeAuthor]) -> Result<()> {
+    match_pool!(pool, p => {
+        let mut tx = p.begin().await?;
+        sqlx::query("DELETE FROM post_authors WHERE post_id = $1")
+            .bind(post_id)
+            .execute(&mut *tx)
+            .await?;
+        for (position, author) in authors.iter().enumerate() {
+            let slug = author.slug();
+            sqlx::query(FREE_ORPHAN_SLUG)
+                .bind(&slug)
+                .bind(&author.name)
+                .execute(&mut *tx)
+                .await?;
+            let taken_by: Option<String> =
+                sqlx::query_scalar("SELECT name FROM authors WHERE slug = $1 AND name <> $2")
+                    .bind(&slug)
+                    .bind(&author.name)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            if let Some(taken_by) = taken_by {
+                return err(&format!(
+                    "❌ Author {} has the slug `{}` of author {}, give one of them a different `slug` in its package.json.",
+                    author.name, slug, taken_by
+                ));
+            }
+            let author_id: i32 = sqlx::query_scalar(AUTHOR_QUERY)
+                .bind(&author.name)
+                .bind(&slug)
+                .bind(&author.email)
+                .bind(&author.url)
+                .bind(&author.avatar)
+                .bind(&author.bio)
+                .bind(author.socials.as_ref().map(Json))
+                .fetch_one(&mut *tx)
+                .await?;
+            // An author listed twice keeps their first position.
+            sqlx::query(
+                "INSERT INTO post_authors (post_id, author_id, position) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            )
+            .bind(post_id)
+            .bind(author_id)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+    });
+    Ok(())
+}
+
+//=====================================================================================================================
+/// 🧹 Remove authors no post lists anymore, once every post being built has been written.
+pub async fn delete_orphan_authors(pool: &FoilPool) -> Result<()> {
+    match_pool!(pool, p => {
+        sqlx::query(DELETE_ORPHAN_AUTHORS).execute(p).await?;
+    });
+    Ok(())
 }
 
 //=====================================================================================================================
 /// 🧼 Clean the database of any stale/missing foil projects.
-pub async fn clean_database(pool: Pool<Postgres>) -> Result<()> {
+pub async fn clean_database(pool: FoilPool) -> Result<()> {
     // For each foil in the database, verify its corresponding output files exist.
     // This is a matter of first checking if its metadata exists, then verifying if its `package.json` exists.
-    let clean_stream = sqlx::query("SELECT id, root_path, permalink FROM posts")
-        .try_map(|row: PgRow| {
-            Ok((
-                row.try_get::<i32, _>(0).unwrap_or_default(),
-                row.try_get::<String, _>(1).unwrap_or_default(),
-                row.try_get::<String, _>(2).unwrap_or_default(),
-            ))
-        })
-        .fetch(&pool)
-        .map_ok(
-            |(id, root_path, permalink)| match PathBuf::from_str(&root_path) {
-                Ok(p) => {
-                    let package_path = p.join("package.json");
-                    let package_exists = package_path.exists();
-                    let meta_exists = p.join("foil-meta.json").exists();
-                    if !package_exists || !meta_exists {
-                        id
-                    } else {
-                        match read_foil_package(&package_path) {
-                            Ok(pack) => {
-                                if pack.foil.permalink == permalink {
-                                    -1
-                                } else {
-                                    id
-                                }
+    let posts: Vec<(i32, String, String)> = match_pool!(&pool, p => {
+        sqlx::query_as("SELECT id, root_path, permalink FROM posts")
+            .fetch_all(p)
+            .await?
+    });
+    let clean_posts = posts.into_iter().filter_map(|(id, root_path, permalink)| {
+        match PathBuf::from_str(&root_path) {
+            Ok(p) => {
+                let package_path = p.join("package.json");
+                let package_exists = package_path.exists();
+                let meta_exists = p.join("foil-meta.json").exists();
+                if !package_exists || !meta_exists {
+                    Some((id, permalink.clone()))
+                } else {
+                    match read_foil_package(&package_path) {
+                        Ok(pack) => {
+                            if pack.foil.permalink == permalink {
+                                None
+                            } else {
+                                Some((id, permalink.clone()))
                             }
-                            Err(_e) => id,
                         }
+                        Err(_e) => Some((id, permalink.clone())),
                     }
                 }
-                _ => -1,
-            },
-        )
-        .filter(|x| future::ready(x.is_ok() && *x.as_ref().unwrap() != -1));
-    let clean_ids = clean_stream.collect::<Vec<_>>().await;
-    for clean_id_result in clean_ids {
-        match clean_id_result {
-            Ok(id) => {
-                let _q = sqlx::query("DELETE FROM posts WHERE id = $1")
-                    .bind(id)
-                    .execute(&pool)
-                    .await?;
             }
-            _ => (),
+            _ => None,
         }
+    });
+    let mut clean_permalinks: Vec<String> = vec![];
+    for (id, permalink) in clean_posts {
+        match_pool!(&pool, p => {
+            sqlx::query("DELETE FROM posts WHERE id = $1")
+                .bind(id)
+                .execute(p)
+                .await?;
+        });
+        clean_permalinks.push(permalink);
+    }
+    if !clean_permalinks.is_empty() {
+        // Deleted posts take their author links with them, leaving authors who no longer wrote anything.
+        delete_orphan_authors(&pool).await?;
+        notify_posts_changed(&pool, &clean_permalinks).await;
     }
 
     Ok(())
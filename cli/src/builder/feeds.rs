@@ -0,0 +1,292 @@
+use super::atom::write_atom;
+use super::json_feed::write_json_feed;
+use super::rss::write_rss;
+use chrono::{DateTime, Utc};
+use path_slash::PathBufExt;
+use std::{cmp::Reverse, fs, path::PathBuf};
+
+use crate::misc::get_site_url;
+use crate::query_post::{query_feed_posts, query_posts};
+use foil_core::{absolute_url, permalink_glob_to_regex, Author, FoilPool, SQLPost};
+
+//=====================================================================================================================
+/// A syndication format foil can write feeds in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeedFormat {
+    /// RSS 2.0, written to `rss.xml`.
+    Rss,
+    /// Atom 1.0, written to `atom.xml`.
+    Atom,
+    /// JSON Feed 1.1, written to `feed.json`.
+    JsonFeed,
+}
+
+impl FeedFormat {
+    /// Parse a feed format from its name in a foil's `feeds` list.
+    pub fn from_name(name: &str) -> Option<FeedFormat> {
+        match name.to_lowercase().as_str() {
+            "rss" => Some(FeedFormat::Rss),
+            "atom" => Some(FeedFormat::Atom),
+            "json" => Some(FeedFormat::JsonFeed),
+            _ => None,
+        }
+    }
+
+    /// The file this feed format is written to in a foil's output folder.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "rss.xml",
+            FeedFormat::Atom => "atom.xml",
+            FeedFormat::JsonFeed => "feed.json",
+        }
+    }
+
+    /// The MIME type feeds link to this feed format with.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Rss => "application/rss+xml",
+            FeedFormat::Atom => "application/atom+xml",
+            FeedFormat::JsonFeed => "application/feed+json",
+        }
+    }
+}
+
+//=====================================================================================================================
+/// A feed declared by a foil post, ready to be written in any of its formats.
+pub struct Feed {
+    /// The post that declares this feed.
+    pub post: SQLPost,
+    /// Posts matching this feed's RSS globs, newest first.
+    pub items: Vec<SQLPost>,
+    /// Every format this feed is written in, and the permalink it's written to.
+    pub links: Vec<(FeedFormat, String)>,
+    /// The most recent modified date of this feed's post and items.
+    pub date_modified: DateTime<Utc>,
+    /// The public base URL of the site, without a trailing slash.
+    pub site_url: String,
+}
+
+impl Feed {
+    /// The absolute URL of this feed in a given format.
+    pub fn link(&self, format: FeedFormat) -> String {
+        self.absolute_url(&feed_permalink(&self.post, format))
+    }
+
+    /// The absolute URL of a permalink on this feed's site.
+    pub fn absolute_url(&self, permalink: &str) -> String {
+        absolute_url(&self.site_url, permalink)
+    }
+
+    /// The enclosure of a post's cover with an absolute URL, if it has one.
+    pub fn cover_enclosure(&self, post: &SQLPost) -> Option<FeedEnclosure> {
+        get_cover_enclosure(post).map(|enclosure| FeedEnclosure {
+            url: self.absolute_url(&enclosure.url),
+            ..enclosure
+        })
+    }
+
+    /// The other formats this feed is available in.
+    pub fn alternates(&self, format: FeedFormat) -> Vec<&(FeedFormat, String)> {
+        self.links.iter().filter(|(f, _)| *f != format).collect()
+    }
+
+    /// The authors of a given post, falling back to this feed's authors and then foil itself.
+    pub fn authors_of(&self, post: &SQLPost) -> Vec<Author> {
+        if !post.authors.0.is_empty() {
+            post.authors.0.clone()
+        } else if !self.post.authors.0.is_empty() {
+            self.post.authors.0.clone()
+        } else {
+            vec![default_author()]
+        }
+    }
+}
+
+//=====================================================================================================================
+/// A post's cover, attached to feed items as an enclosure.
+pub struct FeedEnclosure {
+    pub url: String,
+    pub length: u64,
+    pub mime_type: String,
+}
+
+/// The author used when a feed has none.
+pub fn default_author() -> Author {
+    Author {
+        name: "Foil".to_string(),
+        email: "hi@foil.email".to_string(),
+        url: "/".to_string(),
+        ..Default::default()
+    }
+}
+
+pub fn get_cover_permalink_and_path(post: &SQLPost) -> (String, PathBuf) {
+    let default_cover = "".to_string();
+    let cover = post.covers.first().unwrap_or(&default_cover);
+    let mut root_string = post.root_path.clone();
+    if !(root_string.ends_with("/") || root_string.ends_with("\\")) {
+        root_string += "/";
+    }
+    let cover_relative_permalink = cover.replacen(&post.permalink, "", 1);
+    let cover_rel = if cover_relative_permalink.starts_with("/") {
+        cover_relative_permalink.replacen("/", "", 1)
+    } else {
+        cover_relative_permalink.clone()
+    };
+
+    let root_path = PathBuf::from(root_string);
+    let cover_rel_path = PathBuf::from(&cover_rel);
+    let cover_path = root_path.join(cover_rel_path);
+    (cover.clone(), cover_path.clone())
+}
+
+/// The enclosure of a post's cover, if it has one.
+fn get_cover_enclosure(post: &SQLPost) -> Option<FeedEnclosure> {
+    if post.covers.is_empty() {
+        return None;
+    }
+    let (cover, cover_server_path) = get_cover_permalink_and_path(post);
+    let length = match fs::metadata(&cover_server_path) {
+        Ok(meta) => meta.len(),
+        Err(_e) => 0,
+    };
+    let cover_ext = cover_server_path.extension().unwrap_or_default();
+    let mime_type = cover_mime_type(cover_ext.to_str().unwrap_or_default()).to_string();
+    Some(FeedEnclosure {
+        url: cover,
+        length,
+        mime_type,
+    })
+}
+
+/// The MIME type of a cover from its file extension.
+fn cover_mime_type(extension: &str) -> &'static str {
+    match extension.to_lowercase().as_str() {
+        "jpg" | "jpeg" | "jfif" | "pjpeg" | "pjp" => "image/jpeg",
+        "png" => "image/png",
+        "apng" => "image/apng",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" | "svgz" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" | "cur" => "image/x-icon",
+        "tif" | "tiff" => "image/tiff",
+        "jxl" => "image/jxl",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The permalink a post's feed is served from in a given format, next to its other outputs.
+fn feed_permalink(post: &SQLPost, format: FeedFormat) -> String {
+    let output_path = PathBuf::from(&post.output_path);
+    let relative_path = output_path
+        .strip_prefix(&post.root_path)
+        .unwrap_or(&output_path);
+    PathBuf::from(&post.permalink)
+        .join(relative_path)
+        .join(format.file_name())
+        .to_slash()
+        .unwrap_or_default()
+        .to_string()
+        .replace("\\", "/")
+}
+
+//=====================================================================================================================
+/// 🌊 Build the feeds of every post that declares RSS globs, in each of its formats, written to each post's output path.
+pub async fn build_feeds(pool: FoilPool) {
+    let feed_posts = match query_feed_posts(&pool).await {
+        Ok(v) => v,
+        Err(e) => {
+            if cfg!(debug_assertions) {
+                println!("{:?}", e);
+            }
+            return;
+        }
+    };
+    if feed_posts.is_empty() {
+        return;
+    }
+    let site_url = get_site_url();
+    for feed_post in feed_posts {
+        let feed = query_feed(&pool, feed_post, &site_url).await;
+        write_feed(&feed);
+    }
+}
+
+//=====================================================================================================================
+/// Gather every post matching a given post's publicly exposed RSS globs.
+async fn query_feed(pool: &FoilPool, post: SQLPost, site_url: &str) -> Feed {
+    // 🥬 Gather every post matching this feed's globs, newest first:
+    let mut items: Vec<SQLPost> = vec![];
+    for permalink in post.rss.iter() {
+        let permalink_regex = permalink_glob_to_regex(permalink);
+        for found_item in query_posts(pool, permalink_regex).await.unwrap_or(vec![]) {
+            if found_item.id != post.id && !items.iter().any(|i| i.id == found_item.id) {
+                items.push(found_item);
+            }
+        }
+    }
+    items.sort_by_key(|item| Reverse(item.date_published));
+
+    let date_modified = items
+        .iter()
+        .map(|item| item.date_modified)
+        .fold(post.date_modified, |acc, d| acc.max(d));
+
+    // Unknown formats are skipped, and RSS is kept as the default.
+    let mut formats: Vec<FeedFormat> = vec![];
+    for format in post.feeds.iter() {
+        match FeedFormat::from_name(format) {
+            Some(f) if !formats.contains(&f) => formats.push(f),
+            Some(_) => (),
+            None => println!(
+                "❌ Unknown feed format {} in {}, skipping.",
+                format, post.permalink
+            ),
+        }
+    }
+    if formats.is_empty() {
+        formats.push(FeedFormat::Rss);
+    }
+    let links = formats
+        .iter()
+        .map(|f| (*f, absolute_url(site_url, &feed_permalink(&post, *f))))
+        .collect();
+
+    Feed {
+        post,
+        items,
+        links,
+        date_modified,
+        site_url: site_url.to_string(),
+    }
+}
+
+//=====================================================================================================================
+/// Write a feed in each of its formats to its post's output path.
+fn write_feed(feed: &Feed) {
+    for (format, _) in feed.links.iter() {
+        let contents = match format {
+            FeedFormat::Rss => write_rss(feed),
+            FeedFormat::Atom => write_atom(feed),
+            FeedFormat::JsonFeed => write_json_feed(feed),
+        };
+        let feed_out_path = PathBuf::new()
+            .join(&feed.post.root_path)
+            .join(&feed.post.output_path)
+            .join(format.file_name());
+        let write_result = fs::write(&feed_out_path, contents);
+        if write_result.is_err() {
+            println!("❌ Failed to write {:?} feed output.", format);
+        } else {
+            println!(
+                "🌊 Successfully generated {:?} feed to {}.",
+                format,
+                feed_out_path.to_str().unwrap_or_default()
+            );
+        }
+    }
+}
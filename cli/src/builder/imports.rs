@@ -0,0 +1,668 @@
+use super::nodejs::find_all_imports_node;
+use super::static_assets::FoilFile;
+use crate::misc::NODE_IMPORT_RESOLVER;
+use lazy_static::lazy_static;
+use lexiclean::Lexiclean;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Extensions tried, in order, when an import specifier doesn't include one.
+const RESOLVE_EXTENSIONS: [&str; 10] = [
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts", "mdx", "json",
+];
+
+lazy_static! {
+    // 📜 JavaScript/TypeScript: `import a from "b"`, `export { a } from "b"`, `import type { a } from "b"`.
+    static ref JS_FROM: Regex =
+        Regex::new(r#"(?:^|[^.\w$])(?:import|export)\s[^'";]*?\bfrom\s*['"]([^'"\n]+)['"]"#).unwrap();
+    // `import "b"`.
+    static ref JS_SIDE_EFFECT: Regex =
+        Regex::new(r#"(?:^|[^.\w$])import\s*['"]([^'"\n]+)['"]"#).unwrap();
+    // `import("b")` and `require("b")`.
+    static ref JS_CALL: Regex =
+        Regex::new(r#"(?:^|[^.\w$])(?:import|require)\s*\(\s*['"`]([^'"`\n]+)['"`]\s*\)"#).unwrap();
+    // 📝 MDX only treats `import`/`export` statements at the start of a line as ESM.
+    static ref MDX_FROM: Regex =
+        Regex::new(r#"(?m)^[ \t]*(?:import|export)\s[^'";]*?\bfrom\s*['"]([^'"\n]+)['"]"#).unwrap();
+    static ref MDX_SIDE_EFFECT: Regex =
+        Regex::new(r#"(?m)^[ \t]*import\s*['"]([^'"\n]+)['"]"#).unwrap();
+    static ref MDX_CODE_FENCE: Regex =
+        Regex::new(r"(?ms)^[ \t]*(```|~~~).*?^[ \t]*(```|~~~)").unwrap();
+    // 🎨 CSS: `@import "b"` and `@import url(b)`.
+    static ref CSS_IMPORT: Regex =
+        Regex::new(r#"@import\s+(?:url\(\s*)?['"]?([^'")\s;]+)['"]?"#).unwrap();
+}
+
+//=====================================================================================================================
+/// The kinds of source files the import resolver knows how to read.
+#[derive(Clone, Copy, PartialEq)]
+enum SourceKind {
+    Script,
+    Mdx,
+    Css,
+    Other,
+}
+
+impl SourceKind {
+    fn from_path(path: &Path) -> SourceKind {
+        let ext = path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        match ext.as_str() {
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "mts" | "cts" => SourceKind::Script,
+            "mdx" | "md" => SourceKind::Mdx,
+            "css" | "scss" | "sass" | "less" => SourceKind::Css,
+            _ => SourceKind::Other,
+        }
+    }
+}
+
+//=====================================================================================================================
+/// 🔎 Find all imports of a given main JS/TS file's dependency tree.
+/// Set `FOIL_NODE_RESOLVER=1` to use the foil builder's Node.js resolver instead.
+pub fn find_all_imports(main: String, root_path: &Path) -> Vec<FoilFile> {
+    let imports = if env::var(NODE_IMPORT_RESOLVER).is_ok_and(|v| v == "1") {
+        find_all_imports_node(&main, root_path)
+    } else {
+        resolve_imports(root_path, &root_path.join(&main))
+    };
+
+    let mut foil_files: Vec<FoilFile> = vec![];
+    for import in imports {
+        foil_files.push(FoilFile::from_path(&import));
+    }
+
+    // Include <root_path>/package.json:
+    foil_files.push(FoilFile::from_path(&root_path.join("package.json")));
+
+    // Sort by path and modified date:
+    foil_files.sort_by(|a, b| a.path.cmp(&b.path));
+    foil_files.sort_by_key(|k| k.modified_date.timestamp());
+
+    foil_files
+}
+
+//=====================================================================================================================
+/// Walk the import graph of a given main file, returning every local file it depends on, including itself.
+/// Packages in `node_modules` aren't included.
+pub fn resolve_imports(root_path: &Path, main: &Path) -> Vec<PathBuf> {
+    let main = main.lexiclean();
+    if !main.is_file() {
+        return vec![];
+    }
+    let mut resolver = ImportResolver::new(root_path);
+    let mut resolved: Vec<PathBuf> = vec![];
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+    let mut queue: VecDeque<PathBuf> = VecDeque::new();
+    visited.insert(main.clone());
+    queue.push_back(main);
+    while let Some(file) = queue.pop_front() {
+        for specifier in find_specifiers(&file) {
+            if let Some(import) = resolver.resolve(&file, &specifier) {
+                if visited.insert(import.clone()) {
+                    queue.push_back(import);
+                }
+            }
+        }
+        resolved.push(file);
+    }
+    resolved
+}
+
+//=====================================================================================================================
+/// Read a source file and list the import specifiers it references.
+fn find_specifiers(file: &Path) -> Vec<String> {
+    let kind = SourceKind::from_path(file);
+    if kind == SourceKind::Other {
+        return vec![];
+    }
+    let source = match fs::read_to_string(file) {
+        Ok(v) => v,
+        Err(_) => return vec![],
+    };
+    let patterns: Vec<&Regex> = match kind {
+        SourceKind::Script => vec![&JS_FROM, &JS_SIDE_EFFECT, &JS_CALL],
+        SourceKind::Mdx => vec![&MDX_FROM, &MDX_SIDE_EFFECT],
+        _ => vec![&CSS_IMPORT],
+    };
+    let code = match kind {
+        SourceKind::Script => strip_comments(&source, true),
+        SourceKind::Mdx => MDX_CODE_FENCE.replace_all(&source, "").to_string(),
+        _ => strip_comments(&source, false),
+    };
+    let mut specifiers = vec![];
+    for pattern in patterns {
+        for captures in pattern.captures_iter(&code) {
+            let specifier = captures[1].trim().to_string();
+            // Template literals with expressions can't be resolved statically.
+            if !specifier.is_empty() && !specifier.contains("${") {
+                specifiers.push(specifier);
+            }
+        }
+    }
+    specifiers
+}
+
+//=====================================================================================================================
+/// Replace comments in a source file with whitespace, leaving strings intact. CSS only has block comments.
+fn strip_comments(source: &str, line_comments: bool) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut quote: Option<char> = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == q || (c == '\n' && q != '`') {
+                // Only template literals span multiple lines.
+                quote = None;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) if line_comments => {
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                    }
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+                out.push(' ');
+            }
+            ('"', _) | ('\'', _) | ('`', _) => {
+                quote = Some(c);
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+//=====================================================================================================================
+/// `compilerOptions` of a `tsconfig.json` used to resolve bare specifiers.
+#[derive(Clone, Default)]
+struct TsConfigPaths {
+    /// Absolute `baseUrl`, if any.
+    base_url: Option<PathBuf>,
+    /// Directory `paths` are relative to, either `baseUrl` or the `tsconfig.json` folder.
+    paths_base: PathBuf,
+    /// `paths` aliases and their substitutions.
+    paths: Vec<(String, Vec<String>)>,
+}
+
+//=====================================================================================================================
+/// Resolves import specifiers to files following Node.js and TypeScript's rules.
+struct ImportResolver {
+    root_path: PathBuf,
+    /// `tsconfig.json` settings cached by directory.
+    tsconfigs: HashMap<PathBuf, Option<TsConfigPaths>>,
+}
+
+impl ImportResolver {
+    fn new(root_path: &Path) -> ImportResolver {
+        ImportResolver {
+            root_path: root_path.lexiclean(),
+            tsconfigs: HashMap::new(),
+        }
+    }
+
+    /// Resolve a specifier imported from a given file, or `None` if it's a package or can't be found.
+    fn resolve(&mut self, importer: &Path, specifier: &str) -> Option<PathBuf> {
+        // Strip loader queries and hashes (eg. `./shader.wgsl?raw`).
+        let specifier = specifier.split(['?', '#']).next().unwrap_or_default();
+        let importer_dir = importer.parent().unwrap_or(&self.root_path).to_path_buf();
+        let resolved = if specifier.starts_with("./")
+            || specifier.starts_with("../")
+            || specifier == "."
+            || specifier == ".."
+        {
+            resolve_file_or_directory(&importer_dir.join(specifier))
+        } else if specifier.starts_with('/') {
+            resolve_file_or_directory(&self.root_path.join(specifier.trim_start_matches('/')))
+        } else if specifier.contains(':') || specifier.starts_with('~') {
+            // URLs, `node:` builtins and `~package` CSS imports.
+            None
+        } else {
+            self.resolve_tsconfig_paths(&importer_dir, specifier)
+        };
+        resolved.filter(|p| !is_node_module(p))
+    }
+
+    /// Resolve a bare specifier using the closest `tsconfig.json`'s `paths` and `baseUrl`.
+    fn resolve_tsconfig_paths(&mut self, importer_dir: &Path, specifier: &str) -> Option<PathBuf> {
+        let tsconfig = self.find_tsconfig(importer_dir)?;
+        for (alias, substitutions) in tsconfig.paths.iter() {
+            let wildcard = match alias.split_once('*') {
+                Some((prefix, suffix)) => {
+                    if specifier.len() < prefix.len() + suffix.len()
+                        || !specifier.starts_with(prefix)
+                        || !specifier.ends_with(suffix)
+                    {
+                        continue;
+                    }
+                    &specifier[prefix.len()..specifier.len() - suffix.len()]
+                }
+                None => {
+                    if alias != specifier {
+                        continue;
+                    }
+                    ""
+                }
+            };
+            for substitution in substitutions {
+                let candidate = tsconfig
+                    .paths_base
+                    .join(substitution.replacen('*', wildcard, 1));
+                if let Some(found) = resolve_file_or_directory(&candidate) {
+                    return Some(found);
+                }
+            }
+        }
+        tsconfig
+            .base_url
+            .as_ref()
+            .and_then(|base_url| resolve_file_or_directory(&base_url.join(specifier)))
+    }
+
+    /// Find the closest `tsconfig.json` between a directory and the foil root path.
+    fn find_tsconfig(&mut self, dir: &Path) -> Option<TsConfigPaths> {
+        let dir = dir.to_path_buf();
+        if let Some(cached) = self.tsconfigs.get(&dir) {
+            return cached.clone();
+        }
+        let tsconfig_path = dir.join("tsconfig.json");
+        let found = if tsconfig_path.is_file() {
+            read_tsconfig(&tsconfig_path, 0)
+        } else if dir == self.root_path || !dir.starts_with(&self.root_path) {
+            None
+        } else {
+            match dir.parent() {
+                Some(parent) => self.find_tsconfig(parent),
+                None => None,
+            }
+        };
+        self.tsconfigs.insert(dir, found.clone());
+        found
+    }
+}
+
+//=====================================================================================================================
+/// Read the `baseUrl` and `paths` of a `tsconfig.json`, following relative `extends`.
+fn read_tsconfig(path: &Path, depth: u32) -> Option<TsConfigPaths> {
+    let source = fs::read_to_string(path).ok()?;
+    let json = strip_trailing_commas(&strip_comments(&source, true));
+    let data: Value = serde_json::from_str(&json).ok()?;
+    let dir = path.parent()?.to_path_buf();
+
+    // Start from the extended configuration, if it's a local file.
+    let mut tsconfig = match data["extends"].as_str() {
+        Some(extends) if depth < 8 && (extends.starts_with('.') || extends.starts_with('/')) => {
+            // Like TypeScript, `.json` is appended unless it's already there (eg. `./tsconfig.base`).
+            let extends_path = if extends.ends_with(".json") {
+                dir.join(extends)
+            } else {
+                dir.join(format!("{}.json", extends))
+            };
+            read_tsconfig(&extends_path.lexiclean(), depth + 1)
+        }
+        _ => None,
+    }
+    .unwrap_or(TsConfigPaths {
+        base_url: None,
+        paths_base: dir.clone(),
+        paths: vec![],
+    });
+
+    let compiler_options = &data["compilerOptions"];
+    if let Some(base_url) = compiler_options["baseUrl"].as_str() {
+        let base_url = dir.join(base_url).lexiclean();
+        tsconfig.paths_base = base_url.clone();
+        tsconfig.base_url = Some(base_url);
+    }
+    if let Some(paths) = compiler_options["paths"].as_object() {
+        if tsconfig.base_url.is_none() {
+            tsconfig.paths_base = dir.clone();
+        }
+        tsconfig.paths = paths
+            .iter()
+            .map(|(alias, substitutions)| {
+                let substitutions = substitutions
+                    .as_array()
+                    .map(|v| {
+                        v.iter()
+                            .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                (alias.clone(), substitutions)
+            })
+            .collect();
+    }
+    Some(tsconfig)
+}
+
+/// Remove trailing commas from JSON objects and arrays, which `tsconfig.json` allows.
+fn strip_trailing_commas(json: &str) -> String {
+    lazy_static! {
+        static ref TRAILING_COMMA: Regex = Regex::new(r",(\s*[}\]])").unwrap();
+    }
+    TRAILING_COMMA.replace_all(json, "$1").to_string()
+}
+
+//=====================================================================================================================
+/// Resolve a path as a file, a file missing its extension, or a directory with an index file.
+fn resolve_file_or_directory(path: &Path) -> Option<PathBuf> {
+    let path = path.lexiclean();
+    resolve_file(&path).or_else(|| resolve_directory(&path))
+}
+
+/// Resolve a path as a file, trying TypeScript sources and each of the resolved extensions.
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+
+    // TypeScript lets sources import their compiled `.js` name.
+    let ext = path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let ts_exts: &[&str] = match ext.as_str() {
+        "js" => &["ts", "tsx"],
+        "jsx" => &["tsx"],
+        "mjs" => &["mts"],
+        "cjs" => &["cts"],
+        _ => &[],
+    };
+    for ts_ext in ts_exts {
+        let candidate = path.with_extension(ts_ext);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+
+    // Try each extension appended to the full file name, as in `./component.test` -> `./component.test.tsx`.
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    for resolve_ext in RESOLVE_EXTENSIONS {
+        let candidate = path.with_file_name(format!("{}.{}", file_name, resolve_ext));
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Resolve a directory as a local package with its own entry point, or by its index file.
+/// Like Node.js, a package's `main` is only resolved as a file or index, so it can never point back at itself.
+fn resolve_directory(path: &Path) -> Option<PathBuf> {
+    if !path.is_dir() {
+        return None;
+    }
+    let package_path = path.join("package.json");
+    if package_path.is_file() {
+        let main = fs::read_to_string(&package_path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Value>(&s).ok())
+            .and_then(|v| v["main"].as_str().map(|s| s.to_string()));
+        if let Some(main) = main {
+            let main_path = path.join(main).lexiclean();
+            if main_path != path {
+                if let Some(found) = resolve_file(&main_path).or_else(|| resolve_index(&main_path))
+                {
+                    return Some(found);
+                }
+            }
+        }
+    }
+    resolve_index(path)
+}
+
+/// Resolve a directory's `index` file.
+fn resolve_index(path: &Path) -> Option<PathBuf> {
+    RESOLVE_EXTENSIONS
+        .iter()
+        .map(|resolve_ext| path.join(format!("index.{}", resolve_ext)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// If a path is inside a `node_modules` folder.
+fn is_node_module(path: &Path) -> bool {
+    path.components()
+        .any(|c| c == Component::Normal("node_modules".as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::{test_folder, write_test_file};
+
+    /// Resolve the imports of `main.tsx` in a folder, relative to it.
+    fn resolved(folder: &Path) -> Vec<String> {
+        let mut files: Vec<String> = resolve_imports(folder, &folder.join("main.tsx"))
+            .iter()
+            .map(|file| {
+                file.strip_prefix(folder)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn finds_script_specifiers() {
+        let folder = test_folder("imports-specifiers");
+        write_test_file(
+            &folder,
+            "main.tsx",
+            r#"
+            import a from "./a";
+            import type { B } from './b';
+            export { c } from "./c";
+            import "./side-effect.css";
+            const d = await import("./d");
+            const e = require('./e');
+            const f = import(`./f`);
+            const g = import(`./${name}`);
+            foo.import("./not-an-import");
+            "#,
+        );
+        let specifiers = find_specifiers(&folder.join("main.tsx"));
+        assert_eq!(
+            specifiers,
+            [
+                "./a",
+                "./b",
+                "./c",
+                "./side-effect.css",
+                "./d",
+                "./e",
+                "./f"
+            ]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+
+    #[test]
+    fn finds_mdx_and_css_specifiers() {
+        let folder = test_folder("imports-mdx-css");
+        write_test_file(
+            &folder,
+            "post.mdx",
+            "import Chart from './chart'\n\n```js\nimport fake from './fake'\n```\n\nSome text, import x from './prose'\n",
+        );
+        write_test_file(
+            &folder,
+            "style.css",
+            "@import \"./base.css\";\n/* @import './commented.css'; */\n@import url(theme.css);\n",
+        );
+        assert_eq!(find_specifiers(&folder.join("post.mdx")), ["./chart"]);
+        assert_eq!(
+            find_specifiers(&folder.join("style.css")),
+            ["./base.css", "theme.css"]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+
+    #[test]
+    fn strips_comments_but_not_strings() {
+        assert_eq!(
+            strip_comments("a // comment\nb /* block\n */ c", true),
+            "a \nb \n  c"
+        );
+        assert_eq!(
+            strip_comments("\"// kept\" '/* kept */' `a\n// kept`", true),
+            "\"// kept\" '/* kept */' `a\n// kept`"
+        );
+        assert_eq!(
+            strip_comments("url(//cdn.io/a.css) /* gone */", false),
+            "url(//cdn.io/a.css)  "
+        );
+        assert_eq!(strip_comments("'a\\'// b'", true), "'a\\'// b'");
+    }
+
+    #[test]
+    fn resolves_extensions_and_index_files() {
+        let folder = test_folder("imports-resolve");
+        write_test_file(
+            &folder,
+            "main.tsx",
+            r#"
+            import a from "./a";
+            import b from "./b.js";
+            import c from "./components";
+            import d from "./component.test";
+            import e from "/absolute";
+            import react from "react";
+            import fs from "node:fs";
+            import missing from "./missing";
+            "#,
+        );
+        write_test_file(&folder, "a.ts", "");
+        write_test_file(&folder, "b.tsx", "");
+        write_test_file(&folder, "components/index.tsx", "import x from '../a';");
+        write_test_file(&folder, "component.test.tsx", "");
+        write_test_file(&folder, "absolute.mdx", "");
+        write_test_file(&folder, "node_modules/react/index.js", "");
+        assert_eq!(
+            resolved(&folder),
+            [
+                "a.ts",
+                "absolute.mdx",
+                "b.tsx",
+                "component.test.tsx",
+                "components/index.tsx",
+                "main.tsx"
+            ]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+
+    #[test]
+    fn resolves_package_main_without_looping() {
+        let folder = test_folder("imports-package-main");
+        write_test_file(
+            &folder,
+            "main.tsx",
+            r#"
+            import a from "./lib-a";
+            import b from "./lib-b";
+            import c from "./lib-c/nested";
+            "#,
+        );
+        write_test_file(
+            &folder,
+            "lib-a/package.json",
+            r#"{ "main": "./src/entry" }"#,
+        );
+        write_test_file(&folder, "lib-a/src/entry.ts", "");
+        write_test_file(&folder, "lib-b/package.json", r#"{ "main": "." }"#);
+        write_test_file(&folder, "lib-b/index.js", "");
+        write_test_file(&folder, "lib-c/nested/package.json", r#"{ "main": "../" }"#);
+        write_test_file(&folder, "lib-c/index.ts", "");
+        assert_eq!(
+            resolved(&folder),
+            [
+                "lib-a/src/entry.ts",
+                "lib-b/index.js",
+                "lib-c/index.ts",
+                "main.tsx"
+            ]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+
+    #[test]
+    fn resolves_tsconfig_paths() {
+        let folder = test_folder("imports-tsconfig");
+        write_test_file(
+            &folder,
+            "main.tsx",
+            r#"
+            import a from "@components/button";
+            import b from "@utils";
+            import c from "shared/theme";
+            "#,
+        );
+        write_test_file(
+            &folder,
+            "tsconfig.base.json",
+            r#"{
+                // Comments and trailing commas are allowed.
+                "compilerOptions": { "baseUrl": "./src", },
+            }"#,
+        );
+        write_test_file(
+            &folder,
+            "tsconfig.json",
+            r#"{
+                "extends": "./tsconfig.base",
+                "compilerOptions": {
+                    "paths": {
+                        "@components/*": ["missing/*", "components/*"],
+                        "@utils": ["utils/index.ts"],
+                    },
+                },
+            }"#,
+        );
+        write_test_file(&folder, "src/components/button.tsx", "");
+        write_test_file(&folder, "src/utils/index.ts", "");
+        write_test_file(&folder, "src/shared/theme.ts", "");
+        assert_eq!(
+            resolved(&folder),
+            [
+                "main.tsx",
+                "src/components/button.tsx",
+                "src/shared/theme.ts",
+                "src/utils/index.ts"
+            ]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+}
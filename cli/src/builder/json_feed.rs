@@ -0,0 +1,186 @@
+use super::feeds::{Feed, FeedFormat};
+use foil_core::Author;
+use serde_derive::Serialize;
+
+//=====================================================================================================================
+/// 🌳 A JSON Feed 1.1 document, where optional fields are left out rather than written as `null`.
+#[derive(Serialize)]
+struct JsonFeed {
+    version: &'static str,
+    title: String,
+    home_page_url: String,
+    feed_url: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    authors: Vec<JsonFeedAuthor>,
+    items: Vec<JsonFeedItem>,
+    #[serde(rename = "_foil")]
+    foil: JsonFeedExtension,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem {
+    id: String,
+    url: String,
+    title: String,
+    summary: String,
+    content_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    date_published: String,
+    date_modified: String,
+    authors: Vec<JsonFeedAuthor>,
+    tags: Vec<String>,
+    attachments: Vec<JsonFeedAttachment>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAuthor {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAttachment {
+    url: String,
+    mime_type: String,
+    size_in_bytes: u64,
+}
+
+/// The `_foil` extension, listing the other formats this feed is written in.
+#[derive(Serialize)]
+struct JsonFeedExtension {
+    date_modified: String,
+    alternates: Vec<JsonFeedAlternate>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedAlternate {
+    url: String,
+    mime_type: &'static str,
+}
+
+fn json_authors(feed: &Feed, authors: &[Author]) -> Vec<JsonFeedAuthor> {
+    authors
+        .iter()
+        .map(|author| JsonFeedAuthor {
+            name: author.name.clone(),
+            url: (!author.url.is_empty()).then(|| feed.absolute_url(&author.url)),
+        })
+        .collect()
+}
+
+//=====================================================================================================================
+/// Write a feed as a JSON Feed 1.1 document, with its alternate formats listed in a `_foil` extension.
+pub fn write_json_feed(feed: &Feed) -> String {
+    let root_post = &feed.post;
+
+    // 🥬 Build JSON Feed items:
+    let mut items: Vec<JsonFeedItem> = vec![];
+    for found_item in feed.items.iter() {
+        let enclosure = feed.cover_enclosure(found_item);
+        let link = feed.absolute_url(&found_item.permalink);
+        items.push(JsonFeedItem {
+            id: link.clone(),
+            url: link,
+            title: found_item.title.clone(),
+            summary: found_item.description.clone(),
+            content_text: found_item.description.clone(),
+            image: enclosure.as_ref().map(|enclosure| enclosure.url.clone()),
+            date_published: found_item.date_published.to_rfc3339(),
+            date_modified: found_item.date_modified.to_rfc3339(),
+            authors: json_authors(feed, &feed.authors_of(found_item)),
+            tags: found_item.keywords.clone(),
+            attachments: enclosure
+                .into_iter()
+                .map(|enclosure| JsonFeedAttachment {
+                    url: enclosure.url,
+                    mime_type: enclosure.mime_type,
+                    size_in_bytes: enclosure.length,
+                })
+                .collect(),
+        });
+    }
+
+    let alternates: Vec<JsonFeedAlternate> = feed
+        .alternates(FeedFormat::JsonFeed)
+        .iter()
+        .map(|(format, href)| JsonFeedAlternate {
+            url: href.clone(),
+            mime_type: format.mime_type(),
+        })
+        .collect();
+
+    // 🌳 Define our application JSON feed:
+    let json_feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: root_post.title.clone(),
+        home_page_url: feed.absolute_url(&root_post.permalink),
+        feed_url: feed.link(FeedFormat::JsonFeed),
+        description: root_post.description.clone(),
+        icon: root_post
+            .covers
+            .first()
+            .filter(|cover| !cover.is_empty())
+            .map(|cover| feed.absolute_url(cover)),
+        authors: json_authors(feed, &feed.authors_of(root_post)),
+        items,
+        foil: JsonFeedExtension {
+            date_modified: feed.date_modified.to_rfc3339(),
+            alternates,
+        },
+    };
+    serde_json::to_string_pretty(&json_feed).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use foil_core::{Authors, SQLPost};
+
+    fn post(permalink: &str, authors: Vec<Author>) -> SQLPost {
+        SQLPost {
+            id: 0,
+            name: permalink.to_string(),
+            permalink: permalink.to_string(),
+            title: "Post".to_string(),
+            authors: Authors(authors),
+            description: String::new(),
+            keywords: vec![],
+            covers: vec![],
+            main: String::new(),
+            assets: vec![],
+            rss: vec![],
+            feeds: vec![],
+            date_published: Utc::now(),
+            date_modified: Utc::now(),
+            root_path: String::new(),
+            output_path: String::new(),
+            public_modules: vec![],
+        }
+    }
+
+    #[test]
+    fn empty_optional_fields_are_omitted() {
+        let author = Author {
+            name: "Alain Galvan".to_string(),
+            ..Default::default()
+        };
+        let feed = Feed {
+            post: post("/blog", vec![author.clone()]),
+            items: vec![post("/blog/post1", vec![author])],
+            links: vec![(FeedFormat::JsonFeed, "/blog/feed.json".to_string())],
+            date_modified: Utc::now(),
+            site_url: "https://example.com".to_string(),
+        };
+        let json: serde_json::Value = serde_json::from_str(&write_json_feed(&feed)).unwrap();
+        assert!(json.get("icon").is_none());
+        assert!(json["authors"][0].get("url").is_none());
+        assert!(json["items"][0].get("image").is_none());
+        assert!(json["items"][0]["authors"][0].get("url").is_none());
+        assert_eq!(json["items"][0]["url"], "https://example.com/blog/post1");
+    }
+}
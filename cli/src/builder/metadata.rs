@@ -2,12 +2,16 @@ use super::get_foil_builder_path;
 use super::package_schema::StringMap;
 use super::resolver::Foil;
 use super::static_assets::FoilFile;
+use crate::config::config;
 use crate::BuildMode;
 use async_std::task::{spawn, JoinHandle};
 use serde_derive::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::{fs, io::Read};
 
+/// The current `foil-meta.json` schema version. Version 1 added per file sizes and content hashes.
+pub const FOIL_METADATA_VERSION: u32 = 1;
+
 //=====================================================================================================================
 /// Metadata for a given foil project.
 #[derive(Default, Serialize, Deserialize, Debug)]
@@ -32,6 +36,8 @@ pub struct FoilMetadata {
 pub struct FoilMetadataStatus {
     /// If any source files have changed.
     pub files_changed: bool,
+    /// If source files were touched without their contents changing, so their metadata is stale.
+    pub files_touched: bool,
     /// If the runtime has changed.
     pub runtime_changed: bool,
     /// If any public modules have changed.
@@ -53,42 +59,59 @@ impl FoilMetadata {
         if path.exists() {
             let foil_lock_file = fs::File::open(&path);
             let mut contents = String::new();
-            match foil_lock_file {
-                Ok(mut foil_lock) => {
-                    foil_lock.read_to_string(&mut contents).unwrap_or_default();
-                    metadata = match serde_json::from_str(&contents) {
-                        Ok(v) => v,
-                        _ => FoilMetadata::default(),
-                    };
-                }
-                Err(_) => (),
+            if let Ok(mut foil_lock) = foil_lock_file {
+                foil_lock.read_to_string(&mut contents).unwrap_or_default();
+                metadata = serde_json::from_str(&contents).unwrap_or_default();
             }
         }
         metadata.files.sort_by(|a, b| a.path.cmp(&b.path));
+        metadata.upgrade();
         metadata
     }
 
+    /// ⬆️ Upgrade metadata written by an older version of foil to the current schema.
+    fn upgrade(&mut self) {
+        if self.version >= FOIL_METADATA_VERSION {
+            return;
+        }
+        // Version 0 only stored modified dates. Files that haven't been modified since are hashed as they are now,
+        // everything else is left without a hash so it's treated as changed.
+        for file in self.files.iter_mut() {
+            let current_file = FoilFile::from_path(&PathBuf::from(&file.path));
+            if file.modified_date == current_file.modified_date {
+                file.size = current_file.size;
+                file.hash = current_file.content_hash();
+            }
+        }
+        self.version = FOIL_METADATA_VERSION;
+    }
+
     /// Verify if source files have changed, this can happen if there's additional files or any existing files have been modified.
-    fn verify_source_files(&self, foil: &Foil) -> bool {
+    /// Files whose modified date and size match are assumed unchanged, otherwise their contents are hashed and compared.
+    /// Returns (files changed, files touched without changes).
+    fn verify_source_files(&self, foil: &Foil) -> (bool, bool) {
         let mut source_files = foil.source_files.clone();
         if self.files.len() != source_files.len() || (source_files.is_empty()) {
-            return true;
+            return (true, false);
         }
         // Both sorted lists should be equivalent:
-        let mut lists_match = true;
+        let mut files_touched = false;
         source_files.sort_by(|a, b| a.path.cmp(&b.path));
-        for i in 0..=(source_files.len() - 1) {
-            let mut path_equal = source_files[i].path.eq(&self.files[i].path);
-            path_equal &= source_files[i]
-                .modified_date
-                .eq(&self.files[i].modified_date);
-            path_equal &= PathBuf::from(&self.files[i].path).exists();
-            lists_match &= path_equal;
-            if !lists_match {
-                return true;
+        for (source_file, meta_file) in source_files.iter().zip(self.files.iter()) {
+            if !source_file.path.eq(&meta_file.path) || !PathBuf::from(&meta_file.path).exists() {
+                return (true, false);
             }
+            // 🏎️ Fast path, the file hasn't been touched since it was last built.
+            if source_file.stat_matches(meta_file) {
+                continue;
+            }
+            // 🔏 Otherwise only count it as changed if its contents changed.
+            if meta_file.hash.is_empty() || source_file.content_hash() != meta_file.hash {
+                return (true, false);
+            }
+            files_touched = true;
         }
-        false
+        (false, files_touched)
     }
 
     /// Verify if the SystemJS runtime has been updated. This can happen if either the builder uses a newer version of SystemJS.
@@ -97,9 +120,7 @@ impl FoilMetadata {
             let system_path = foil.output_path.join(PathBuf::from("system.js"));
             let foil_builder_path = get_foil_builder_path();
             let builder_package_path = foil_builder_path.join(PathBuf::from("package.json"));
-            let file_result = fs::File::open(&builder_package_path);
-            if file_result.is_ok() {
-                let mut file = file_result.unwrap();
+            if let Ok(mut file) = fs::File::open(&builder_package_path) {
                 let mut contents = String::new();
                 let file_read_result = file.read_to_string(&mut contents);
                 if file_read_result.is_ok() {
@@ -109,7 +130,7 @@ impl FoilMetadata {
                     };
                     let current_systemjs_version = match data["dependencies"]["systemjs"].clone() {
                         serde_json::Value::String(s) => s,
-                        _ => "=6.15.1".to_string(),
+                        _ => config().systemjs_version.clone(),
                     };
                     return self.systemjs_version != current_systemjs_version
                         || !system_path.exists();
@@ -156,13 +177,14 @@ impl FoilMetadata {
         {
             return FoilMetadataStatus {
                 files_changed: true,
+                files_touched: false,
                 runtime_changed: true,
                 public_modules_changed: true,
             };
         }
 
         // 🧱 Verify if source files have changed first:
-        let source_files_changed = self.verify_source_files(foil);
+        let (source_files_changed, files_touched) = self.verify_source_files(foil);
         // 🏎️ Verify SystemJS runtime:
         let runtime_changed = self.verify_runtime(foil);
         // 📚 Check if public vendor modules need to be built.
@@ -170,6 +192,7 @@ impl FoilMetadata {
 
         FoilMetadataStatus {
             files_changed: source_files_changed,
+            files_touched,
             runtime_changed,
             public_modules_changed,
         }
@@ -178,6 +201,7 @@ impl FoilMetadata {
 
 //=====================================================================================================================
 /// Write a foil project's corresponding metadata file, used to determine is there's been changes to the project.
+/// Source files are hashed as they're written.
 pub async fn write_foil_metadata(
     path: PathBuf,
     source_files: Vec<FoilFile>,
@@ -188,9 +212,13 @@ pub async fn write_foil_metadata(
     spawn(async move {
         let file = fs::File::create(path).unwrap();
         let mut writer = std::io::BufWriter::new(file);
+        let mut files = source_files.to_vec();
+        for file in files.iter_mut() {
+            file.hash = file.content_hash();
+        }
         let metadata = FoilMetadata {
-            version: 0,
-            files: source_files.to_vec(),
+            version: FOIL_METADATA_VERSION,
+            files,
             systemjs_version: systemjs_version.to_string(),
             public_modules: public_modules.clone(),
             mode: (if build_mode == BuildMode::Release {
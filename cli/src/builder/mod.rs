@@ -1,17 +1,28 @@
 pub mod build_mode;
 
+mod atom;
 mod database;
+mod feeds;
+mod imports;
+mod json_feed;
 mod metadata;
 mod nodejs;
 pub mod package_schema;
 mod resolver;
 mod rss;
+mod sitemap;
 mod static_assets;
-
-use crate::error::Result;
-use crate::misc::connect_db;
+mod watch;
+
+use crate::config::config;
+use crate::db::check_schema;
+use crate::error::{err, Result};
+use crate::misc::{check_site_url, connect_db};
+use crate::query_post::query_post;
+use async_std::channel::Sender;
 pub use build_mode::BuildMode;
-use database::{clean_database, udpate_foil_db};
+use database::{clean_database, delete_orphan_authors, udpate_foil_db};
+use foil_core::FoilPool;
 use metadata::{write_foil_metadata, FoilMetadata};
 use nodejs::compile_foil_main;
 pub use resolver::read_foil_package;
@@ -21,12 +32,22 @@ use std::env;
 use std::path::PathBuf;
 use std::process::Child;
 use std::time::Instant;
+use watch::watch_foils;
+pub use watch::BuildEvent;
 
 //=====================================================================================================================
 /// Process the current working directory for Foil projects.
-pub async fn build(build_mode: BuildMode) -> Result<()> {
+/// When `watch` is set, keeps rebuilding foils as they change, reporting progress to `events` if given.
+pub async fn build(
+    build_mode: BuildMode,
+    watch: bool,
+    events: Option<Sender<BuildEvent>>,
+) -> Result<()> {
+    check_site_url(&build_mode)?;
+
     // 📚 Configure database...
     let pool = connect_db().await?;
+    check_schema(&pool).await?;
     let cwd = env::current_dir().unwrap_or_default();
 
     // ⏳ Start build benchmark:
@@ -36,7 +57,7 @@ pub async fn build(build_mode: BuildMode) -> Result<()> {
     clean_database(pool.clone()).await?;
 
     let mut resolved_foils: Vec<(Foil, FoilMetadata)> = vec![];
-    resolve_foils(cwd, &mut resolved_foils).await?;
+    resolve_foils(cwd.clone(), &mut resolved_foils).await?;
 
     // Process resolved foils...
     let resolved_foil_len = resolved_foils.len();
@@ -46,14 +67,40 @@ pub async fn build(build_mode: BuildMode) -> Result<()> {
         println!("🎡 Processing {} file(s).", &resolved_foil_len)
     }
 
+    build_foils(&pool, &build_mode, &mut resolved_foils).await?;
+
+    let elapsed = now.elapsed();
+    println!("⏲️ Build time: {:.2?}", elapsed);
+
+    if let Some(events) = &events {
+        let _ = events.send(BuildEvent::Built).await;
+    }
+
+    // 👁️ Keep rebuilding foils as they change.
+    if watch {
+        watch_foils(cwd, pool, build_mode, &resolved_foils, events).await?;
+    }
+    Ok(())
+}
+
+//=====================================================================================================================
+/// Verify, update and compile a given set of resolved foils, then write the RSS output for this foil project.
+async fn build_foils(
+    pool: &FoilPool,
+    build_mode: &BuildMode,
+    resolved_foils: &mut [(Foil, FoilMetadata)],
+) -> Result<()> {
+    let resolved_foil_len = resolved_foils.len();
     let mut update_futures = vec![];
     let mut write_futures = vec![];
+    let mut failed_foils: Vec<PathBuf> = vec![];
     let mut build_children: Vec<Child> = vec![];
     let mut public_module_cache: HashMap<String, Vec<String>> = HashMap::new();
     let root_foil_permalink = "/".to_string();
     for (i, (resolved_foil, foil_metadata)) in resolved_foils.iter_mut().enumerate() {
         // 🧱 Check if foil has changed.
-        let foil_changed = foil_metadata.verify(&resolved_foil, build_mode.clone());
+        let foil_changed = foil_metadata.verify(resolved_foil, build_mode.clone());
+        let write_metadata = foil_changed.changed() || foil_changed.files_touched;
 
         // Recompile and update the database if there's been changes to source files.
         if foil_changed.changed() {
@@ -68,9 +115,12 @@ pub async fn build(build_mode: BuildMode) -> Result<()> {
 
             // ⏳ Only wait for frontend posts.
             if resolved_foil.frontend {
-                let _ = update_future.await;
+                if let Err(e) = update_future.await.await {
+                    println!("{}", e);
+                    failed_foils.push(resolved_foil.root_path.clone());
+                }
             } else {
-                update_futures.push(update_future);
+                update_futures.push((resolved_foil.root_path.clone(), update_future));
             }
 
             // 🛠️ Build foil if needed.
@@ -78,33 +128,34 @@ pub async fn build(build_mode: BuildMode) -> Result<()> {
                 // We must inherit public modules from the root foil project.
                 if !resolved_foil.frontend {
                     if !public_module_cache.contains_key(&root_foil_permalink) {
-                        let found: (i32, Vec<String>) = sqlx::query_as(
-                            "SELECT id, public_modules FROM posts WHERE permalink = $1",
-                        )
-                        .bind(&root_foil_permalink)
-                        .fetch_one(&pool)
-                        .await
-                        .unwrap_or((-1, vec![]));
-                        public_module_cache.insert(root_foil_permalink.clone(), found.1);
+                        let found = query_post(pool, root_foil_permalink.clone())
+                            .await
+                            .map(|post| post.public_modules)
+                            .unwrap_or(vec![]);
+                        public_module_cache.insert(root_foil_permalink.clone(), found);
                     }
                     let cached_public_modules = public_module_cache.get(&root_foil_permalink);
-                    match cached_public_modules {
-                        Some(v) => {
-                            for parent_module in v {
-                                resolved_foil.public_modules.push(parent_module.to_string());
-                            }
+                    if let Some(v) = cached_public_modules {
+                        for parent_module in v {
+                            resolved_foil.public_modules.push(parent_module.to_string());
                         }
-                        _ => (),
                     }
                 }
+                // 🧵 Keep at most the configured number of builders running at once.
+                if build_children.len() >= config().build_concurrency {
+                    let mut oldest = build_children.remove(0);
+                    oldest.wait().expect("Failed to run Foil Builder...");
+                }
                 // Build project.
-                let child = compile_foil_main(build_mode.clone(), &resolved_foil, foil_changed)?;
+                let child = compile_foil_main(build_mode.clone(), resolved_foil, foil_changed)?;
                 build_children.push(child);
             }
+        }
 
-            // 🍥 Write out metadata to local lock file.
+        // 🍥 Write out metadata to local lock file, also refreshing it if files were only touched.
+        if write_metadata {
             let foil_lock_path = resolved_foil.root_path.join("foil-meta.json");
-            let systemjs_version = "=6.15.1".to_string();
+            let systemjs_version = config().systemjs_version.clone();
             let write_future = write_foil_metadata(
                 foil_lock_path,
                 resolved_foil.source_files.clone(),
@@ -112,22 +163,45 @@ pub async fn build(build_mode: BuildMode) -> Result<()> {
                 resolved_foil.public_modules_map.clone(),
                 build_mode.clone(),
             );
-            write_futures.push(write_future);
+            write_futures.push((resolved_foil.root_path.clone(), write_future));
         }
     }
 
     // Join all async threads here.
-    futures::future::join_all(update_futures).await;
-    futures::future::join_all(write_futures).await;
+    let (update_paths, update_futures): (Vec<PathBuf>, Vec<_>) = update_futures.into_iter().unzip();
+    let updates = futures::future::join_all(update_futures).await;
+    for (root_path, update) in update_paths.into_iter().zip(updates) {
+        if let Err(e) = update.await {
+            println!("{}", e);
+            failed_foils.push(root_path);
+        }
+    }
+    // Renamed or removed authors are only dropped once no post being written can link them anymore.
+    delete_orphan_authors(pool).await?;
+    // Foils that failed to be written keep their old metadata, so they're processed again next time.
+    let write_futures = write_futures
+        .into_iter()
+        .filter(|(root_path, _)| !failed_foils.contains(root_path))
+        .map(|(_, write_future)| write_future);
+    for write in futures::future::join_all(write_futures).await {
+        write.await;
+    }
 
     // 🌊 Write the RSS output for this foil project.
-    rss::build_rss(pool.clone()).await;
+    feeds::build_feeds(pool.clone()).await;
+
+    // 🗺️ Write the sitemap of every published post.
+    sitemap::build_sitemap(pool.clone()).await;
 
     for mut child in build_children {
         child.wait().expect("Failed to run Foil Builder...");
     }
-    let elapsed = now.elapsed();
-    println!("⏲️ Build time: {:.2?}", elapsed);
+    if !failed_foils.is_empty() {
+        return err(&format!(
+            "❌ {} foil(s) couldn't be written to the database.",
+            failed_foils.len()
+        ));
+    }
     Ok(())
 }
 //=====================================================================================================================
@@ -141,6 +215,5 @@ pub fn get_foil_folder_path() -> PathBuf {
 //=====================================================================================================================
 /// Get the builder folder path.
 pub fn get_foil_builder_path() -> PathBuf {
-    let foil_builder_path = get_foil_folder_path().join(PathBuf::from("builder"));
-    foil_builder_path
+    get_foil_folder_path().join(PathBuf::from("builder"))
 }
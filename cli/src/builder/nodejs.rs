@@ -1,24 +1,21 @@
-use chrono::{DateTime, Utc};
-
 use super::get_foil_builder_path;
 use super::metadata::FoilMetadataStatus;
 use super::resolver::Foil;
-use super::static_assets::FoilFile;
 use crate::{BuildMode, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 
 //=====================================================================================================================
 // NPM is somewhat buggy at times, and requires the extension on windows.
 #[cfg(windows)]
-const NPM: &'static str = "npm.cmd";
+const NPM: &str = "npm.cmd";
 
 #[cfg(not(windows))]
-const NPM: &'static str = "npm";
+const NPM: &str = "npm";
 
 //=====================================================================================================================
-/// 🔎 Find all imports of a given main JS/TS file's dependency tree.
-pub fn find_all_imports(main: String, root_path: &PathBuf) -> Vec<FoilFile> {
+/// 🔎 Find all imports of a given main JS/TS file's dependency tree with the foil builder's Node.js resolver.
+pub fn find_all_imports_node(main: &str, root_path: &Path) -> Vec<PathBuf> {
     // The foil builder exists next to the current executable:
     let foil_builder_path = get_foil_builder_path();
 
@@ -29,7 +26,7 @@ pub fn find_all_imports(main: String, root_path: &PathBuf) -> Vec<FoilFile> {
         .to_string()
         .replace("\\", "/");
     let main_abs_str = root_path
-        .join(PathBuf::from(&main))
+        .join(PathBuf::from(main))
         .to_str()
         .unwrap_or("/")
         .to_string()
@@ -40,62 +37,8 @@ pub fn find_all_imports(main: String, root_path: &PathBuf) -> Vec<FoilFile> {
         .output()
         .unwrap();
     let out_string = String::from_utf8(find.stdout).unwrap_or("[]".to_string());
-    // TODO: should this just be string paths, and we figure out the modified date?
-    let data: Vec<String> = match serde_json::from_str(&out_string) {
-        Ok(v) => v,
-        Err(_er) => {
-            vec![]
-        }
-    };
-
-    let mut foil_files: Vec<FoilFile> = vec![];
-    for import in data {
-        let import_path = PathBuf::from(import);
-        let clean_path = import_path
-            .to_str()
-            .unwrap_or("/")
-            .to_string()
-            .replace("\\", "/");
-
-        let modified_date = match import_path.metadata() {
-            Ok(m) => {
-                DateTime::<Utc>::from(m.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
-            }
-            _ => DateTime::<Utc>::MIN_UTC,
-        };
-        let foil_file = FoilFile {
-            path: clean_path,
-            modified_date,
-        };
-        foil_files.push(foil_file);
-    }
-
-    // Include <root_path>/package.json:
-    {
-        let package_path = root_path.join(PathBuf::from("package.json"));
-        let clean_path = package_path
-            .to_str()
-            .unwrap_or("/")
-            .to_string()
-            .replace("\\", "/");
-
-        let modified_date = match package_path.metadata() {
-            Ok(m) => {
-                DateTime::<Utc>::from(m.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH))
-            }
-            _ => DateTime::<Utc>::MIN_UTC,
-        };
-        let foil_file = FoilFile {
-            path: clean_path,
-            modified_date,
-        };
-        foil_files.push(foil_file);
-    }
-    // Sort by path and modified date:
-    foil_files.sort_by(|a, b| a.path.cmp(&b.path));
-    foil_files.sort_by_key(|k| k.modified_date.timestamp());
-
-    foil_files
+    let data: Vec<String> = serde_json::from_str(&out_string).unwrap_or_default();
+    data.into_iter().map(PathBuf::from).collect()
 }
 
 //=====================================================================================================================
@@ -106,7 +49,7 @@ pub fn compile_foil_main(
     foil_changed: FoilMetadataStatus,
 ) -> Result<Child> {
     // ⤵️ Install all dependencies for this project if they don't exist.
-    let _ci = match Command::new(NPM)
+    match Command::new(NPM)
         .current_dir(&resolved_foil.root_path)
         .arg("ci")
         .arg("--include=dev")
@@ -116,7 +59,7 @@ pub fn compile_foil_main(
         Err(_e) => {
             // There may not be a package-lock.json file, add one:
             println!("Failed to run `npm ci`, running `npm i`:");
-            let _i = match Command::new(NPM)
+            match Command::new(NPM)
                 .current_dir(&resolved_foil.root_path)
                 .arg("i")
                 .arg("--include=dev")
@@ -1,4 +1,6 @@
+use crate::config::config;
 use chrono::{DateTime, Utc};
+use foil_core::SocialLink;
 use serde_derive::{Deserialize, Serialize};
 pub type StringMap = std::collections::HashMap<String, String>;
 
@@ -42,15 +44,49 @@ pub struct NodePackage {
 }
 
 //=====================================================================================================================
-/// Node.js authors.
-#[derive(Serialize, Deserialize, Clone, Debug, sqlx::Type)]
+/// 🎇 An author as written in a `package.json`, either its `author` or one of its `contributors`.
+/// Authors with the same name are the same person across every foil post, so details left out of one post
+/// are kept from the others, while an empty one such as `"bio": ""` or `"socials": []` clears it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct NodeAuthor {
+    /// The name of this author.
     pub name: String,
-    #[serde(default = "default_empty_str")]
-    pub email: String,
-    #[serde(default = "default_empty_str")]
-    pub url: String,
+
+    /// The URL-safe name of this author's page, derived from their name if missing.
+    pub slug: Option<String>,
+
+    /// The email address of this author.
+    pub email: Option<String>,
+
+    /// The website of this author.
+    pub url: Option<String>,
+
+    /// A URL to a picture of this author.
+    pub avatar: Option<String>,
+
+    /// A short biography of this author.
+    pub bio: Option<String>,
+
+    /// Links to this author's profiles elsewhere, such as `[{ "name": "GitHub", "url": "https://github.com/..." }]`.
+    pub socials: Option<Vec<SocialLink>>,
+}
+
+impl NodeAuthor {
+    /// 🏷️ The slug of this author's page, lowercase ASCII letters and numbers separated by dashes (eg. `Jane O'Brien` is `jane-o-brien`).
+    /// Any other character separates words, the same as the migration that gave existing authors their slugs.
+    pub fn slug(&self) -> String {
+        let source = match &self.slug {
+            Some(slug) if !slug.trim().is_empty() => slug,
+            _ => &self.name,
+        };
+        source
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_ascii_lowercase())
+            .collect::<Vec<String>>()
+            .join("-")
+    }
 }
 
 //=======================================================================================================================
@@ -68,7 +104,7 @@ pub struct FoilConfig {
     #[serde(default = "default_current_date")]
     pub date_published: DateTime<Utc>,
 
-    /// Public assets for this foil post.
+    /// Public assets for this foil post. Rules starting with `!` exclude files, and the last matching rule wins.
     #[serde(default = "default_assets")]
     pub assets: Vec<String>,
 
@@ -84,9 +120,26 @@ pub struct FoilConfig {
     #[serde(default = "default_false")]
     pub frontend: bool,
 
-    /// RSS glob path to export posts from.
-    #[serde(default = "default_rss_vec")]
-    pub rss: Vec<String>,
+    /// RSS glob paths to export posts from, where `*` stays within one segment and `**` crosses segments.
+    /// Every foil that declares them gets its own feed.
+    /// If missing, only the root foil gets a feed, of `/blog/*` or the `[posts]` globs of foil.toml.
+    #[serde(default)]
+    pub rss: Option<Vec<String>>,
+
+    /// Feed formats to generate from the RSS globs, any of `rss`, `atom` and `json`.
+    #[serde(default = "default_feeds_vec")]
+    pub feeds: Vec<String>,
+}
+
+impl FoilConfig {
+    /// The RSS globs of this foil post, defaulting to `/blog/*` for the root foil only.
+    pub fn rss_globs(&self) -> Vec<String> {
+        match &self.rss {
+            Some(rss) => rss.clone(),
+            None if self.permalink == "/" => default_rss_vec(),
+            None => default_empty_vec(),
+        }
+    }
 }
 
 //=====================================================================================================================
@@ -100,11 +153,15 @@ fn default_empty_str() -> String {
 }
 
 fn default_rss_vec() -> Vec<String> {
-    vec!["/blog/*".to_string()]
+    config().default_rss.clone()
+}
+
+fn default_feeds_vec() -> Vec<String> {
+    vec!["rss".to_string()]
 }
 
 fn default_assets() -> Vec<String> {
-    vec!["assets/**/*".to_string()]
+    config().default_assets.clone()
 }
 
 fn default_empty_vec<T>() -> Vec<T> {
@@ -114,3 +171,43 @@ fn default_empty_vec<T>() -> Vec<T> {
 fn default_current_date() -> DateTime<Utc> {
     chrono::offset::Utc::now()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn author(name: &str, slug: Option<&str>) -> NodeAuthor {
+        NodeAuthor {
+            name: name.to_string(),
+            slug: slug.map(str::to_string),
+            email: None,
+            url: None,
+            avatar: None,
+            bio: None,
+            socials: None,
+        }
+    }
+
+    #[test]
+    fn slugs_are_derived_from_names() {
+        assert_eq!(author("Jane O'Brien", None).slug(), "jane-o-brien");
+        assert_eq!(author("Seán O'Brien", None).slug(), "se-n-o-brien");
+        assert_eq!(author("Alain Galvan", None).slug(), "alain-galvan");
+        assert_eq!(author("  Jane   Doe, PhD. ", None).slug(), "jane-doe-phd");
+        assert_eq!(author("R2-D2", None).slug(), "r2-d2");
+    }
+
+    #[test]
+    fn explicit_slugs_are_normalized() {
+        assert_eq!(author("Alain Galvan", Some("Alain_G")).slug(), "alain-g");
+        assert_eq!(author("Alain Galvan", Some("  ")).slug(), "alain-galvan");
+    }
+
+    #[test]
+    fn names_without_ascii_letters_or_numbers_have_empty_slugs() {
+        assert_eq!(author("—", None).slug(), "");
+        assert_eq!(author("🦀✨", None).slug(), "");
+        assert_eq!(author("山田太郎", None).slug(), "");
+        assert_eq!(author("🦀✨", Some("ferris")).slug(), "ferris");
+    }
+}
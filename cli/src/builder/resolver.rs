@@ -1,8 +1,8 @@
+use super::imports::find_all_imports;
 use super::metadata::FoilMetadata;
-use super::nodejs::find_all_imports;
 use super::package_schema::{NodeAuthor, NodePackage, StringMap};
 use super::static_assets::{build_static_assets, FoilFile, StaticAsset};
-use crate::Result;
+use crate::error::{err, Result};
 use async_std::task::{spawn, JoinHandle};
 use chrono::{DateTime, Utc};
 use std::cmp::Ordering;
@@ -67,8 +67,11 @@ pub struct Foil {
     /// Resolved public modules and their corresponding version.
     pub public_modules_map: StringMap,
 
-    /// The permalink glob to generate this project's RSS feed.
+    /// The permalink globs to generate this project's RSS feed from, empty if it has no feed.
     pub rss: Vec<String>,
+
+    /// The feed formats generated from this project's RSS globs.
+    pub feeds: Vec<String>,
 }
 
 impl Foil {
@@ -107,7 +110,7 @@ impl Foil {
 /// ❌ Determine if a directory entry is a foil project. Skip folders/files used when building (node_modules, target, hidden folders):
 fn is_foil_package(entry: &DirEntry) -> bool {
     let file_name = entry.file_name().to_str().unwrap_or_default();
-    if file_name.len() <= 0
+    if file_name.is_empty()
         || file_name == "node_modules"
         || file_name == "target"
         || file_name.char_indices().next().unwrap().1 == '.'
@@ -117,27 +120,24 @@ fn is_foil_package(entry: &DirEntry) -> bool {
     true
 }
 //=====================================================================================================================
-/// Traverse a given folder and its files for foil projects, and process them.
-pub async fn resolve_foil(cur_path_package: PathBuf) -> JoinHandle<Option<(Foil, FoilMetadata)>> {
+/// Read and process the foil project of a given `package.json`, skipping it if it isn't readable.
+pub async fn resolve_foil(
+    cur_path_package: PathBuf,
+) -> JoinHandle<Result<Option<(Foil, FoilMetadata)>>> {
     spawn(async move {
         let cur_path_root = cur_path_package.parent().unwrap().to_path_buf();
 
         // 🌟 We've found a foil project, attempt to foilify it and process it later.
-        match read_foil_package(&cur_path_package) {
-            Ok(v) => {
-                match process_foil_project(v, &cur_path_root) {
-                    Ok(resolved_foil) => {
-                        // 🔒 Load foil-meta file and compare source file path/modified date.
-                        let foil_lock_path = resolved_foil.root_path.join("foil-meta.json");
-                        let foil_metadata = FoilMetadata::open(foil_lock_path);
-                        return Some((resolved_foil, foil_metadata));
-                    }
-                    Err(_er) => (),
-                };
-            }
-            Err(_er) => (),
+        let package = match read_foil_package(&cur_path_package) {
+            Ok(v) => v,
+            Err(_er) => return Ok(None),
         };
-        None
+        let resolved_foil = process_foil_project(package, &cur_path_root)?;
+
+        // 🔒 Load foil-meta file and compare source file path/modified date.
+        let foil_lock_path = resolved_foil.root_path.join("foil-meta.json");
+        let foil_metadata = FoilMetadata::open(foil_lock_path);
+        Ok(Some((resolved_foil, foil_metadata)))
     })
 }
 
@@ -152,7 +152,7 @@ pub async fn resolve_foils(
     for entry in WalkDir::new(path)
         .follow_links(true)
         .into_iter()
-        .filter_entry(|e| is_foil_package(e))
+        .filter_entry(is_foil_package)
         .filter_map(|e| e.ok())
     {
         let file_name = entry.file_name().to_str().unwrap_or_default();
@@ -166,9 +166,8 @@ pub async fn resolve_foils(
     // Join threads:
     let joined_futures = futures::future::join_all(resolved_foil_futures).await;
     for foil_future in joined_futures {
-        match foil_future.await {
-            Some(v) => resolved_foils.push(v),
-            None => (),
+        if let Some(v) = foil_future.await? {
+            resolved_foils.push(v);
         }
     }
 
@@ -191,7 +190,10 @@ pub async fn resolve_foils(
 /// Process a given foil package and resolve it.
 fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
     // 🚢 Resolve source file imports:
-    let source_files = find_all_imports(package.main.clone(), &path);
+    let source_files = find_all_imports(package.main.clone(), path);
+
+    // 🌊 Resolve which posts this foil's RSS feed includes, if any.
+    let rss = package.foil.rss_globs();
 
     // 🚪 Determine output path, can either be current foil package path, or specified by the project configuration.
     let mut output_path = path.clone();
@@ -204,7 +206,7 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
     }
 
     // 📅 Either the current date or the date written in the foil package is the published date.
-    let date_published = DateTime::<Utc>::from(package.foil.date_published);
+    let date_published = package.foil.date_published;
     // The most recent modified source file is the public modified date.
     let date_modified = source_files
         .iter()
@@ -255,18 +257,12 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
     if !package.foil.public_modules.is_empty() {
         for m in package.foil.public_modules.iter() {
             let dependencies = package.dependencies.clone().unwrap_or_default();
-            if !dependencies.is_empty() {
-                if dependencies.contains_key(m) {
-                    let pair = dependencies.get_key_value(m).unwrap();
-                    public_modules_map.insert(pair.0.to_string(), pair.1.to_string());
-                }
+            if let Some(pair) = dependencies.get_key_value(m) {
+                public_modules_map.insert(pair.0.to_string(), pair.1.to_string());
             }
             let dev_dependencies = package.dev_dependencies.clone().unwrap_or_default();
-            if !dev_dependencies.is_empty() {
-                if dev_dependencies.contains_key(m) {
-                    let pair = dev_dependencies.get_key_value(m).unwrap();
-                    public_modules_map.insert(pair.0.to_string(), pair.1.to_string());
-                }
+            if let Some(pair) = dev_dependencies.get_key_value(m) {
+                public_modules_map.insert(pair.0.to_string(), pair.1.to_string());
             }
         }
     }
@@ -275,8 +271,26 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
     if !package.contributors.is_empty() {
         authors.append(&mut package.contributors.clone());
     }
+    let authors: Vec<NodeAuthor> = authors
+        .into_iter()
+        .map(|mut author| {
+            author.name = author.name.trim().to_string();
+            author
+        })
+        .filter(|author| !author.name.is_empty())
+        .collect();
+    for author in authors.iter() {
+        if author.slug().is_empty() {
+            return err(&format!(
+                "❌ The author \"{}\" in {} has no ASCII letters or numbers to make the URL of their page from.\nGive them a \"slug\" in that package.json, such as {{ \"name\": \"{}\", \"slug\": \"jane-doe\" }}.",
+                author.name,
+                path.join("package.json").display(),
+                author.name
+            ));
+        }
+    }
 
-    let name = if package.name.len() > 0 {
+    let name = if !package.name.is_empty() {
         package.name
     } else {
         package
@@ -296,7 +310,7 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
         permalink: package.foil.permalink,
         title: package.foil.title,
         description: package.description,
-        authors: authors,
+        authors,
         keywords: package.keywords,
         covers,
         main: package.main,
@@ -309,7 +323,8 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
         output_path,
         frontend: package.foil.frontend,
         public_modules_map,
-        rss: package.foil.rss,
+        rss,
+        feeds: package.foil.feeds,
     };
 
     Ok(foil)
@@ -318,7 +333,7 @@ fn process_foil_project(package: NodePackage, path: &PathBuf) -> Result<Foil> {
 //=====================================================================================================================
 /// Read a given file as a foil package.
 pub fn read_foil_package(file_path: &PathBuf) -> Result<NodePackage> {
-    let file = match File::open(&file_path) {
+    let file = match File::open(file_path) {
         Ok(v) => v,
         Err(e) => {
             println!("{:?}", e);
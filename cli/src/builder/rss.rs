@@ -1,153 +1,131 @@
-use super::package_schema::NodeAuthor;
+use super::feeds::{get_cover_permalink_and_path, Feed, FeedFormat};
+use foil_core::Author;
+use rss::extension::atom::{AtomExtensionBuilder, Link};
+use rss::extension::dublincore::DublinCoreExtensionBuilder;
 use rss::{
-    Category, CategoryBuilder, ChannelBuilder, EnclosureBuilder, ImageBuilder, Item, ItemBuilder,
+    Category, CategoryBuilder, ChannelBuilder, EnclosureBuilder, GuidBuilder, Image, ImageBuilder,
+    Item, ItemBuilder,
 };
-use sqlx::{postgres::Postgres, Pool};
-use std::{fs, path::PathBuf};
 
-use crate::query_post::{query_post, query_posts, SQLPost};
+/// RSS 2.0 people are email addresses, optionally followed by a name, so authors without an email are left out.
+fn rss_person(author: &Author) -> Option<String> {
+    if author.email.is_empty() {
+        None
+    } else {
+        Some(format!("{} ({})", author.email, author.name))
+    }
+}
 
-fn get_cover_permalink_and_path(post: &SQLPost) -> (String, PathBuf) {
-    let default_cover = "".to_string();
-    let cover = post.covers.get(0).unwrap_or(&default_cover);
-    let mut root_string = post.root_path.clone();
-    if !(root_string.ends_with("/") || root_string.ends_with("\\")) {
-        root_string += "/";
+/// The channel image of a feed, from its post's cover. RSS 2.0 limits image sizes to 144x400, so larger or
+/// unknown sizes are left out.
+fn rss_image(feed: &Feed) -> Option<Image> {
+    let root_post = &feed.post;
+    if root_post.covers.is_empty() {
+        return None;
     }
-    let cover_relative_permalink = cover.replacen(&post.permalink, "", 1);
-    let cover_rel = if cover_relative_permalink.starts_with("/") {
-        cover_relative_permalink.replacen("/", "", 1)
-    } else {
-        cover_relative_permalink.clone()
+    let (cover, cover_server_path) = get_cover_permalink_and_path(root_post);
+    let (width, height) = match imagesize::size(&cover_server_path) {
+        Ok(v) if v.width > 0 && v.width <= 144 && v.height > 0 && v.height <= 400 => {
+            (Some(v.width.to_string()), Some(v.height.to_string()))
+        }
+        _ => (None, None),
     };
-
-    let root_path = PathBuf::from(root_string);
-    let cover_rel_path = PathBuf::from(&cover_rel);
-    let cover_path = root_path.join(cover_rel_path);
-    (cover.clone(), cover_path.clone())
+    let image = ImageBuilder::default()
+        .url(feed.absolute_url(&cover))
+        .link(feed.absolute_url(&root_post.permalink))
+        .title(root_post.title.clone())
+        .width(width)
+        .height(height)
+        .description(Some(root_post.title.clone()))
+        .build();
+    Some(image)
 }
 
-/// Build an RSS document based on the root permalink's publicly exposed RSS subdirectories.
-pub async fn build_rss(pool: Pool<Postgres>) {
-    let found_root_post: Result<SQLPost, sqlx::Error> = query_post(&pool, "/".to_string()).await;
-    match found_root_post {
-        Err(e) => {
-            if cfg!(debug_assertions) {
-                println!("{:?}", e);
-            }
-        }
-        Ok(root_post) => {
-            let default_author = NodeAuthor {
-                name: "Foil".to_string(),
-                email: "hi@foil.email".to_string(),
-                url: "/".to_string(),
-            };
-            let author = root_post.authors.0.get(0).unwrap_or(&default_author);
-            let mut categories: Vec<Category> = vec![];
-            for tag in root_post.keywords.clone() {
-                let cat = CategoryBuilder::default().name(tag).build();
-                categories.push(cat);
-            }
-            let (cover, cover_server_path) = get_cover_permalink_and_path(&root_post);
-            let (width, height) = match imagesize::size(&cover_server_path) {
-                Ok(v) => (v.width, v.height),
-                Err(_ie) => (0, 0),
-            };
-            let image = ImageBuilder::default()
-                .url(cover)
-                .link(author.url.clone())
-                .title(root_post.title.clone())
-                .width(Some(width.to_string()))
-                .height(Some(height.to_string()))
-                .description(Some(root_post.title.clone()))
-                .build();
-            let copyright = "Copyright ".to_string() + &author.name + " All Rights Reserved";
+//=====================================================================================================================
+/// Write a feed as an RSS 2.0 document, linking to itself and its alternate formats with Atom links.
+pub fn write_rss(feed: &Feed) -> String {
+    let root_post = &feed.post;
+    let authors = feed.authors_of(root_post);
+    let author = &authors[0];
+    let mut categories: Vec<Category> = vec![];
+    for tag in root_post.keywords.clone() {
+        let cat = CategoryBuilder::default().name(tag).build();
+        categories.push(cat);
+    }
+    let copyright = "Copyright ".to_string() + &author.name + " All Rights Reserved";
 
-            // 🌳 Define our application RSS channel:
-            let mut channel = ChannelBuilder::default()
-                .title(root_post.title.clone())
-                .link(author.url.clone())
-                .description(root_post.description)
-                .copyright(Some(copyright))
-                .managing_editor(Some(author.name.clone()))
-                .webmaster(Some(author.name.clone()))
-                .categories(categories)
-                .image(Some(image))
-                .ttl(Some("1200".to_string()))
-                .build();
+    // 🔗 Link to this feed and its alternate formats:
+    let mut links: Vec<Link> = vec![];
+    for (format, href) in feed.links.iter() {
+        let mut link = Link::default();
+        link.set_href(href.clone());
+        link.set_rel(if *format == FeedFormat::Rss {
+            "self"
+        } else {
+            "alternate"
+        });
+        link.set_mime_type(Some(format.mime_type().to_string()));
+        links.push(link);
+    }
+    let atom_ext = AtomExtensionBuilder::default().links(links).build();
 
-            // 🥬 Build RSS Items:
-            let mut items: Vec<Item> = vec![];
-            let permalink = "/blog/*".to_string();
-            let mut permalink_regex = "^".to_string();
-            for char in permalink.chars() {
-                let char_string = char.to_string();
-                let char_str = &char_string;
-                match char {
-                    '/' | '$' | '^' | '+' | '.' | '(' | ')' | '=' | '!' | '|' | ',' | '{' | '}'
-                    | '[' | ']' => {
-                        permalink_regex += "\\";
-                        permalink_regex += char_str;
-                    }
-                    '*' => {
-                        permalink_regex += ".*";
-                    }
-                    _ => permalink_regex += char_str,
-                }
-            }
-            permalink_regex += "$";
-            let found_items: Vec<SQLPost> =
-                query_posts(&pool, permalink_regex).await.unwrap_or(vec![]);
-            for found_item in found_items {
-                let (cover, cover_server_path) = get_cover_permalink_and_path(&found_item);
-                let byte_length = match fs::metadata(&cover_server_path) {
-                    Ok(meta) => {
-                        let s = meta.len();
-                        s
-                    }
-                    Err(_e) => 0,
-                };
-                let cover_ext = cover_server_path.extension().unwrap_or_default();
-                let mime = "image/".to_string() + cover_ext.to_str().unwrap_or_default();
-                let enclosure = EnclosureBuilder::default()
-                    .url(cover)
-                    .length(byte_length.to_string())
-                    .mime_type(mime)
-                    .build();
-                let item_author = found_item.authors.0.get(0).unwrap_or(&default_author);
-                let mut item_categories: Vec<Category> = vec![];
-                for tag in found_item.keywords.clone() {
-                    let cat = CategoryBuilder::default().name(tag).build();
-                    item_categories.push(cat);
-                }
-                let item = ItemBuilder::default()
-                    .title(Some(found_item.title))
-                    .description(Some(found_item.description))
-                    .link(Some(found_item.permalink))
-                    .pub_date(Some(found_item.date_published.to_string()))
-                    .categories(item_categories)
-                    .author(Some(item_author.name.clone()))
-                    .enclosure(Some(enclosure))
-                    .build();
-                items.push(item);
-            }
-            channel.set_items(items);
+    // 🌳 Define our application RSS channel:
+    let mut channel = ChannelBuilder::default()
+        .title(root_post.title.clone())
+        .link(feed.absolute_url(&root_post.permalink))
+        .description(root_post.description.clone())
+        .copyright(Some(copyright))
+        .managing_editor(rss_person(author))
+        .webmaster(rss_person(author))
+        .categories(categories)
+        .image(rss_image(feed))
+        .last_build_date(Some(feed.date_modified.to_rfc2822()))
+        .ttl(Some("1200".to_string()))
+        .atom_ext(Some(atom_ext))
+        .build();
 
-            // Write to output path:
-            let rss_out_path = PathBuf::new()
-                .join(&root_post.root_path)
-                .join(&root_post.output_path)
-                .join("rss.xml");
-            let write_result = fs::write(&rss_out_path, channel.to_string());
-            if write_result.is_err() {
-                println!("❌ Failed to write RSS output.");
-                return;
-            } else {
-                println!(
-                    "🌊 Successfully generated RSS feed to {}.",
-                    rss_out_path.to_str().unwrap_or_default()
-                );
-            }
+    // 🥬 Build RSS Items:
+    let mut items: Vec<Item> = vec![];
+    for found_item in feed.items.iter() {
+        let enclosure = feed.cover_enclosure(found_item).map(|enclosure| {
+            EnclosureBuilder::default()
+                .url(enclosure.url)
+                .length(enclosure.length.to_string())
+                .mime_type(enclosure.mime_type)
+                .build()
+        });
+        let item_authors = feed.authors_of(found_item);
+        let mut item_categories: Vec<Category> = vec![];
+        for tag in found_item.keywords.clone() {
+            let cat = CategoryBuilder::default().name(tag).build();
+            item_categories.push(cat);
         }
+        // RSS 2.0 has no modified date, so it's written as a Dublin Core date.
+        let dublin_core_ext = DublinCoreExtensionBuilder::default()
+            .creators(
+                item_authors
+                    .iter()
+                    .map(|a| a.name.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .dates(vec![found_item.date_modified.to_rfc3339()])
+            .build();
+        let link = feed.absolute_url(&found_item.permalink);
+        let item = ItemBuilder::default()
+            .title(Some(found_item.title.clone()))
+            .description(Some(found_item.description.clone()))
+            .link(Some(link.clone()))
+            .guid(Some(
+                GuidBuilder::default().value(link).permalink(true).build(),
+            ))
+            .pub_date(Some(found_item.date_published.to_rfc2822()))
+            .categories(item_categories)
+            .author(rss_person(&item_authors[0]))
+            .enclosure(enclosure)
+            .dublin_core_ext(Some(dublin_core_ext))
+            .build();
+        items.push(item);
     }
+    channel.set_items(items);
+    channel.to_string()
 }
@@ -0,0 +1,45 @@
+use std::{fs, path::PathBuf};
+
+use crate::misc::get_site_url;
+use crate::query_post::query_post;
+use foil_core::sitemap::{query_sitemap_posts, write_sitemap};
+use foil_core::FoilPool;
+
+//=====================================================================================================================
+/// 🗺️ Build a sitemap of every published post, written next to the root foil's RSS feed.
+/// Sitemaps past 50,000 URLs are split into pages listed by `sitemap.xml`, just as the backend serves them.
+pub async fn build_sitemap(pool: FoilPool) {
+    let root_post = match query_post(&pool, "/".to_string()).await {
+        Ok(v) => v,
+        Err(_e) => {
+            println!("❌ Couldn't find a root foil with permalink /, skipping sitemap.");
+            return;
+        }
+    };
+    let posts = match query_sitemap_posts(&pool).await {
+        Ok(v) => v,
+        Err(e) => {
+            if cfg!(debug_assertions) {
+                println!("{:?}", e);
+            }
+            return;
+        }
+    };
+    let site_url = get_site_url();
+
+    let out_path = PathBuf::new()
+        .join(&root_post.root_path)
+        .join(&root_post.output_path);
+    for (file_name, contents) in write_sitemap(&site_url, &posts) {
+        let sitemap_out_path = out_path.join(file_name);
+        if fs::write(&sitemap_out_path, contents).is_err() {
+            println!("❌ Failed to write sitemap output.");
+            return;
+        }
+    }
+    println!(
+        "🗺️ Successfully generated sitemap of {} posts to {}.",
+        posts.len(),
+        out_path.join("sitemap.xml").to_str().unwrap_or_default()
+    );
+}
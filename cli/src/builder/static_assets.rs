@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
-use glob::glob;
+use foil_core::AssetRule;
 use lexiclean::Lexiclean;
 use path_slash::PathBufExt;
 use serde_derive::{Deserialize, Serialize};
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
 
 use crate::error::Result;
 use crate::return_err;
@@ -17,6 +20,45 @@ pub struct FoilFile {
     pub path: String,
     /// When this file was last modified on the server, used to update it.
     pub modified_date: chrono::DateTime<chrono::Utc>,
+    /// Size of this file in bytes, checked alongside its modified date before hashing.
+    #[serde(default)]
+    pub size: i64,
+    /// SHA-256 digest of this file's contents, empty if it hasn't been hashed yet.
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl FoilFile {
+    /// Describe a file on the server by its path, modified date and size. Its contents are hashed lazily.
+    pub fn from_path(path: &Path) -> FoilFile {
+        let clean_path = path.to_str().unwrap_or("/").to_string().replace("\\", "/");
+        let (modified_date, size) = match path.metadata() {
+            Ok(m) => (
+                DateTime::<Utc>::from(m.modified().unwrap_or(std::time::SystemTime::UNIX_EPOCH)),
+                m.len() as i64,
+            ),
+            _ => (DateTime::<Utc>::MIN_UTC, 0),
+        };
+        FoilFile {
+            path: clean_path,
+            modified_date,
+            size,
+            hash: "".to_string(),
+        }
+    }
+
+    /// If this file's modified date and size still match what's currently on disk.
+    pub fn stat_matches(&self, other: &FoilFile) -> bool {
+        self.modified_date == other.modified_date && self.size == other.size
+    }
+
+    /// 🔏 Hash the current contents of this file, or an empty string if it can't be read.
+    pub fn content_hash(&self) -> String {
+        match fs::read(&self.path) {
+            Ok(bytes) => format!("{:x}", Sha256::digest(bytes)),
+            Err(_) => "".to_string(),
+        }
+    }
 }
 
 //=====================================================================================================================
@@ -33,48 +75,128 @@ pub struct StaticAsset {
     pub modified_date: chrono::DateTime<chrono::Utc>,
 }
 
+//=====================================================================================================================
+/// The folder a glob rule's files are all in, the part of it before its first wildcard (eg. `assets` for `assets/**/*`).
+fn glob_base_folder(rule: &str) -> PathBuf {
+    let wildcard = rule.find(['*', '?', '[', '{', '\\']).unwrap_or(rule.len());
+    match rule[..wildcard].rfind('/') {
+        Some(end) => PathBuf::from(&rule[..end]),
+        None => PathBuf::new(),
+    }
+}
+
 //=====================================================================================================================
 /// Given a directory, convert it to a list of static assets.
+/// Assets are matched with the same globs as the backend's asset whitelist, so the files copied are the files served.
+/// Assets excluded by a negated rule such as `!assets/private/**` are left out.
 pub fn build_static_assets(
     base_path: &PathBuf,
     permalink: &str,
-    paths: &Vec<String>,
+    paths: &[String],
     assets: &mut Vec<StaticAsset>,
 ) -> Result<()> {
-    for asset in paths {
-        let cur_asset = base_path
-            .clone()
-            .join(asset)
-            .lexiclean()
-            .to_slash()
-            .unwrap()
-            .to_string();
+    let rules: Vec<AssetRule> = paths
+        .iter()
+        .filter_map(|asset| AssetRule::new(permalink, asset).ok())
+        .collect();
+    // 📂 Only the folders that could contain matching assets are searched.
+    let mut folders: Vec<PathBuf> = paths
+        .iter()
+        .filter(|asset| !asset.starts_with('!'))
+        .map(|asset| base_path.join(glob_base_folder(asset)).lexiclean())
+        .collect();
+    folders.sort();
+    folders.dedup();
+    let folders: Vec<PathBuf> = folders
+        .iter()
+        .filter(|folder| folder.exists())
+        .filter(|folder| {
+            !folders
+                .iter()
+                .any(|f| f != *folder && folder.starts_with(f))
+        })
+        .cloned()
+        .collect();
 
-        for entry_result in glob(&cur_asset)? {
-            match entry_result {
-                Ok(entry) => {
-                    let relative_path = entry.strip_prefix(base_path.clone()).unwrap();
-                    let joined_path = PathBuf::from(permalink).join(relative_path);
-                    let p: String = joined_path
-                        .to_str()
-                        .unwrap_or("/")
-                        .to_string()
-                        .replace("\\", "/");
-                    let meta = return_err!(entry.metadata(), "Failed to get metadata for asset.");
-                    if meta.is_file() {
-                        assets.push(StaticAsset {
-                            path: entry.to_slash().unwrap().to_string(),
-                            permalink: p,
-                            modified_date: DateTime::<Utc>::from(meta.modified().unwrap()),
-                        });
-                    }
-                }
+    for folder in folders {
+        for entry in WalkDir::new(&folder).follow_links(true).sort_by_file_name() {
+            let entry = match entry {
+                Ok(entry) => entry,
                 Err(_e) => {
-                    println!("❌ Couldn't read asset path {}, skipping.", &cur_asset);
-                    break;
+                    println!(
+                        "❌ Couldn't read asset path {}, skipping.",
+                        folder.display()
+                    );
+                    continue;
                 }
+            };
+            let Ok(relative_path) = entry.path().strip_prefix(base_path) else {
+                continue;
+            };
+            let p = PathBuf::from(permalink)
+                .join(relative_path)
+                .to_slash()
+                .unwrap_or_default()
+                .to_string();
+            if AssetRule::find_match(&rules, &p).is_none_or(|rule| rule.negated) {
+                continue;
+            }
+            let meta = return_err!(entry.metadata(), "Failed to get metadata for asset.");
+            if meta.is_file() {
+                assets.push(StaticAsset {
+                    path: entry.path().to_path_buf().to_slash().unwrap().to_string(),
+                    permalink: p,
+                    modified_date: DateTime::<Utc>::from(meta.modified().unwrap()),
+                });
             }
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::{test_folder, write_test_file};
+
+    #[test]
+    fn assets_match_the_backend_whitelist() {
+        let folder = test_folder("static-assets");
+        for path in [
+            "assets/cover.png",
+            "assets/nested/image.jpg",
+            "assets/private/key.txt",
+            "models/ship.glb",
+            "models/old/ship.glb",
+            "main.tsx",
+        ] {
+            write_test_file(&folder, path, "");
+        }
+        let rules = vec![
+            "assets/**/*".to_string(),
+            "models/*.glb".to_string(),
+            "!assets/private/**".to_string(),
+        ];
+        let mut assets = vec![];
+        build_static_assets(&folder, "/blog/post", &rules, &mut assets).unwrap();
+        let mut permalinks: Vec<String> = assets.into_iter().map(|a| a.permalink).collect();
+        permalinks.sort();
+        assert_eq!(
+            permalinks,
+            [
+                "/blog/post/assets/cover.png",
+                "/blog/post/assets/nested/image.jpg",
+                "/blog/post/models/ship.glb",
+            ]
+        );
+        let _ = fs::remove_dir_all(folder);
+    }
+
+    #[test]
+    fn glob_base_folder_stops_at_the_first_wildcard() {
+        assert_eq!(glob_base_folder("assets/**/*"), PathBuf::from("assets"));
+        assert_eq!(glob_base_folder("a/b/*.png"), PathBuf::from("a/b"));
+        assert_eq!(glob_base_folder("*.png"), PathBuf::new());
+        assert_eq!(glob_base_folder("{a,b}/*"), PathBuf::new());
+    }
+}
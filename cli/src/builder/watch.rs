@@ -0,0 +1,295 @@
+use super::build_foils;
+use super::database::{clean_database, udpate_foil_db};
+use super::metadata::FoilMetadata;
+use super::resolver::{resolve_foil, Foil};
+use crate::error::Result;
+use crate::BuildMode;
+use async_std::channel::{unbounded, Receiver, Sender};
+use async_std::future::timeout;
+use foil_core::{AssetRule, FoilPool};
+use lexiclean::Lexiclean;
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+use path_slash::PathBufExt;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long the file system must stay quiet before a burst of saves is rebuilt.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+//=====================================================================================================================
+/// Events emitted while building and watching foil projects.
+#[derive(Clone, Debug)]
+pub enum BuildEvent {
+    /// The initial build has finished.
+    Built,
+    /// Foils have been rebuilt or removed after changes, with the permalinks of every rebuilt foil.
+    Rebuilt(Vec<String>),
+}
+
+//=====================================================================================================================
+/// The files of a resolved foil that should trigger a rebuild when changed.
+struct WatchedFoil {
+    /// Source files resolved from the main entry file, including its `package.json`.
+    source_files: HashSet<String>,
+    /// The folder of this foil, which its asset rules are relative to.
+    root_path: String,
+    /// Asset rules of this foil, matched the same way as when its assets are copied.
+    assets: Vec<AssetRule>,
+}
+
+impl WatchedFoil {
+    fn new(foil: &Foil) -> WatchedFoil {
+        let source_files = foil
+            .source_files
+            .iter()
+            .map(|f| clean_watch_path(&PathBuf::from(&f.path)))
+            .collect();
+        let assets = foil
+            .assets
+            .iter()
+            .filter_map(|asset| AssetRule::new("", asset).ok())
+            .collect();
+        WatchedFoil {
+            source_files,
+            root_path: clean_watch_path(&foil.root_path),
+            assets,
+        }
+    }
+
+    /// Returns (source changed, asset changed) for a given changed path.
+    fn matches(&self, path: &str) -> (bool, bool) {
+        let source_changed = self.source_files.contains(path);
+        let asset_changed = path
+            .strip_prefix(&self.root_path)
+            .and_then(|relative_path| relative_path.strip_prefix('/'))
+            .and_then(|relative_path| AssetRule::find_match(&self.assets, relative_path))
+            .is_some_and(|rule| !rule.negated);
+        (source_changed, asset_changed)
+    }
+}
+
+fn clean_watch_path(p: &Path) -> String {
+    p.to_path_buf()
+        .lexiclean()
+        .to_slash()
+        .unwrap_or_default()
+        .to_string()
+        .replace("\\", "/")
+}
+
+//=====================================================================================================================
+/// ❌ Skip changes to folders/files used when building (node_modules, target, hidden folders) and foil metadata.
+fn is_ignored_path(cwd: &Path, path: &Path) -> bool {
+    let relative_path = path.strip_prefix(cwd).unwrap_or(path);
+    let ignored_component = relative_path.components().any(|c| match c {
+        Component::Normal(name) => {
+            let name = name.to_str().unwrap_or_default();
+            name == "node_modules" || name == "target" || name.starts_with('.')
+        }
+        _ => false,
+    });
+    ignored_component || path.file_name().is_some_and(|f| f == "foil-meta.json")
+}
+
+//=====================================================================================================================
+/// Wait for the next burst of file system changes, returning once no new events arrive for `WATCH_DEBOUNCE`.
+/// Returns `None` if the watcher has been dropped.
+async fn next_changes(
+    cwd: &Path,
+    rx: &Receiver<notify::Result<Event>>,
+) -> Option<HashSet<PathBuf>> {
+    let mut changed: HashSet<PathBuf> = HashSet::new();
+    let mut collect = |event: notify::Result<Event>| match event {
+        Ok(ev) => match ev.kind {
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) | EventKind::Any => {
+                for path in ev.paths {
+                    if !is_ignored_path(cwd, &path) {
+                        changed.insert(path);
+                    }
+                }
+            }
+            _ => (),
+        },
+        Err(e) => println!("❌ File watcher error: {:?}", e),
+    };
+
+    collect(rx.recv().await.ok()?);
+    loop {
+        match timeout(WATCH_DEBOUNCE, rx.recv()).await {
+            Ok(Ok(event)) => collect(event),
+            Ok(Err(_)) => return None,
+            Err(_) => break,
+        }
+    }
+    Some(changed)
+}
+
+//=====================================================================================================================
+/// 👁️ Watch every resolved foil's source files, `package.json` and assets, rebuilding only the foils that change.
+pub async fn watch_foils(
+    cwd: PathBuf,
+    pool: FoilPool,
+    build_mode: BuildMode,
+    resolved_foils: &[(Foil, FoilMetadata)],
+    events: Option<Sender<BuildEvent>>,
+) -> Result<()> {
+    // Watched foils keyed by their `package.json` path.
+    let mut watched_foils: HashMap<PathBuf, WatchedFoil> = HashMap::new();
+    for (foil, _) in resolved_foils {
+        watched_foils.insert(foil.root_path.join("package.json"), WatchedFoil::new(foil));
+    }
+
+    let (tx, rx) = unbounded();
+    let mut watcher = recommended_watcher(move |event| {
+        let _ = tx.try_send(event);
+    })?;
+    watcher.watch(&cwd, RecursiveMode::Recursive)?;
+    println!(
+        "\n👁️ Watching {} foil(s) for changes, press Ctrl+C to exit.",
+        watched_foils.len()
+    );
+
+    while let Some(changed) = next_changes(&cwd, &rx).await {
+        // 🗺️ Map changed paths to the foils they belong to.
+        let mut affected_packages: HashSet<PathBuf> = HashSet::new();
+        let mut asset_packages: HashSet<PathBuf> = HashSet::new();
+        for path in changed.iter() {
+            // New, modified or removed foil packages are always re-resolved.
+            if path.file_name().is_some_and(|f| f == "package.json") {
+                affected_packages.insert(path.clone());
+            }
+            let path_str = clean_watch_path(path);
+            for (package_path, watched_foil) in watched_foils.iter() {
+                let (source_changed, asset_changed) = watched_foil.matches(&path_str);
+                if source_changed {
+                    affected_packages.insert(package_path.clone());
+                }
+                if asset_changed {
+                    affected_packages.insert(package_path.clone());
+                    asset_packages.insert(package_path.clone());
+                }
+            }
+        }
+        if affected_packages.is_empty() {
+            continue;
+        }
+
+        // ⏳ Start rebuild benchmark:
+        let now = Instant::now();
+
+        // 📦 Re-resolve affected foils, dropping any that were removed.
+        let mut removed_foils = false;
+        let mut rebuilt_foils: Vec<(Foil, FoilMetadata)> = vec![];
+        for package_path in affected_packages {
+            let resolved = if package_path.exists() {
+                match resolve_foil(package_path.clone()).await.await {
+                    Ok(resolved) => resolved,
+                    Err(e) => {
+                        println!("{}", e);
+                        continue;
+                    }
+                }
+            } else {
+                None
+            };
+            match resolved {
+                Some((foil, foil_metadata)) => {
+                    if !watched_foils.contains_key(&package_path) {
+                        println!("✨ Found new foil {}.", &foil.title);
+                    }
+                    watched_foils.insert(package_path.clone(), WatchedFoil::new(&foil));
+                    rebuilt_foils.push((foil, foil_metadata));
+                }
+                None => {
+                    if watched_foils.remove(&package_path).is_some() {
+                        println!(
+                            "🗑️ Removed foil {}.",
+                            package_path.to_str().unwrap_or_default()
+                        );
+                        removed_foils = true;
+                    }
+                }
+            }
+        }
+
+        // 🧼🫧 Remove any foils that no longer exist from the database.
+        if removed_foils {
+            if let Err(e) = clean_database(pool.clone()).await {
+                println!("{}", e);
+            }
+        }
+
+        // 🖼️ Asset changes don't affect source files, but may change covers, so update their posts directly.
+        for (foil, _) in rebuilt_foils.iter() {
+            if asset_packages.contains(&foil.root_path.join("package.json")) {
+                if let Err(e) = udpate_foil_db(foil.clone(), pool.clone()).await.await {
+                    println!("{}", e);
+                }
+            }
+        }
+
+        if !rebuilt_foils.is_empty() {
+            // Frontends must be processed first so other foils inherit their public modules.
+            rebuilt_foils.sort_by_key(|(foil, _)| !foil.frontend);
+            // A broken foil is reported, then watched until it's fixed.
+            if let Err(e) = build_foils(&pool, &build_mode, &mut rebuilt_foils).await {
+                println!("{}", e);
+            }
+        }
+        let elapsed = now.elapsed();
+        println!("⏲️ Rebuild time: {:.2?}", elapsed);
+
+        // 📣 Let any listeners know which foils finished rebuilding.
+        if let Some(events) = &events {
+            if removed_foils || !rebuilt_foils.is_empty() {
+                let permalinks = rebuilt_foils
+                    .iter()
+                    .map(|(foil, _)| foil.permalink.clone())
+                    .collect();
+                let _ = events.send(BuildEvent::Rebuilt(permalinks)).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assets_match_the_copied_assets() {
+        let watched_foil = WatchedFoil {
+            source_files: HashSet::from(["/site/blog/post/main.tsx".to_string()]),
+            root_path: "/site/blog/post".to_string(),
+            assets: [
+                "assets/**/*",
+                "models/*.glb",
+                "!assets/private/**",
+                "{icons,fonts}/*",
+            ]
+            .iter()
+            .map(|asset| AssetRule::new("", asset).unwrap())
+            .collect(),
+        };
+        for (path, asset_changed) in [
+            ("/site/blog/post/assets/cover.png", true),
+            ("/site/blog/post/assets/nested/image.jpg", true),
+            ("/site/blog/post/assets/private/key.txt", false),
+            ("/site/blog/post/models/ship.glb", true),
+            ("/site/blog/post/models/old/ship.glb", false),
+            ("/site/blog/post/icons/logo.svg", true),
+            ("/site/blog/post/fonts/serif.woff2", true),
+            ("/site/blog/post/main.tsx", false),
+            ("/site/blog/other/assets/cover.png", false),
+            ("/site/blog/post-2/assets/cover.png", false),
+        ] {
+            assert_eq!(watched_foil.matches(path).1, asset_changed, "{}", path);
+        }
+        assert_eq!(
+            watched_foil.matches("/site/blog/post/main.tsx"),
+            (true, false)
+        );
+    }
+}
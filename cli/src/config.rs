@@ -0,0 +1,267 @@
+use crate::builder::get_foil_folder_path;
+use crate::error::Result;
+use crate::misc::DATABASE_URL;
+use crate::server::backend_path;
+use clap::ArgMatches;
+use foil_core::config::{find_config_file, read_config_file, ConfigError};
+use foil_core::{redact_db_url, ListenAddress, ServerConfig};
+use serde_derive::Deserialize;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread::available_parallelism;
+use std::time::Duration;
+
+/// The environment variable with the folder foil server logs are written to.
+pub const LOG_DIR: &str = "FOIL_LOG_DIR";
+
+/// The environment variable with how many foil builders may run at once.
+pub const BUILD_CONCURRENCY: &str = "FOIL_BUILD_CONCURRENCY";
+
+/// The environment variable with the path of the foil backend executable.
+pub const BACKEND_PATH: &str = "FOIL_BACKEND_PATH";
+
+const DEFAULT_DATABASE_URL: &str = "postgres://localhost/foil";
+const DEFAULT_LOG_MAX_SIZE_MB: u64 = 10;
+const DEFAULT_LOG_ROTATE_HOURS: u64 = 24;
+const DEFAULT_LOG_RETENTION_DAYS: u64 = 14;
+const DEFAULT_SYSTEMJS_VERSION: &str = "=6.15.1";
+
+static CONFIG: OnceLock<ProjectConfig> = OnceLock::new();
+
+//=====================================================================================================================
+/// The tables of `foil.toml` read by the foil CLI, where every setting is optional.
+/// The `[server]` table is read by `ServerConfig`, shared with the foil backend.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ProjectFile {
+    database: DatabaseFile,
+    build: BuildFile,
+    posts: PostsFile,
+    logs: LogsFile,
+    backend: BackendFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseFile {
+    url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BuildFile {
+    concurrency: Option<usize>,
+    systemjs_version: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PostsFile {
+    rss: Option<Vec<String>>,
+    assets: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BackendFile {
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LogsFile {
+    dir: Option<PathBuf>,
+    max_size_mb: Option<u64>,
+    rotate_hours: Option<u64>,
+    retention_days: Option<u64>,
+}
+
+//=====================================================================================================================
+/// ⚙️ The effective settings of a foil project, from `foil.toml`, then environment variables, then command line flags.
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    /// The configuration file these settings were read from, if any.
+    pub path: Option<PathBuf>,
+    /// The database URL, `postgres://localhost/foil` if not configured.
+    pub database_url: Option<String>,
+    /// Listen addresses, renderer address, timeouts and site URL of the foil server.
+    pub server: ServerConfig,
+    /// The foil backend executable, if not the one next to the foil CLI.
+    pub backend_path: Option<PathBuf>,
+    /// The folder foil server logs are written to.
+    pub log_dir: PathBuf,
+    /// Logs are rotated once they grow this many bytes, never if zero.
+    pub log_max_size: u64,
+    /// Logs are rotated once they're this old, never if zero.
+    pub log_rotate_after: Duration,
+    /// Rotated logs are deleted once they're this old, never if zero.
+    pub log_retention: Duration,
+    /// How many foil builders may run at once.
+    pub build_concurrency: usize,
+    /// The SystemJS version foil runtimes are built with, when the foil builder's `package.json` doesn't pin one.
+    pub systemjs_version: String,
+    /// The RSS globs of the root foil when its `package.json` declares none.
+    pub default_rss: Vec<String>,
+    /// The asset globs of foils whose `package.json` declares none.
+    pub default_assets: Vec<String>,
+}
+
+impl ProjectConfig {
+    /// Load the project configuration, overriding `foil.toml` and the environment with the given command line flags.
+    pub fn load(matches: &ArgMatches) -> Result<ProjectConfig> {
+        let path = match matches.get_one::<String>("config") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => find_config_file(),
+        };
+        let file: ProjectFile = match &path {
+            Some(path) => read_config_file(path)?,
+            None => ProjectFile::default(),
+        };
+        // Relative paths in foil.toml are relative to the file itself.
+        let config_dir = path
+            .as_ref()
+            .and_then(|p| p.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let mut server = ServerConfig::load_from(path.clone())?;
+        if let Some(listen) = matches.get_many::<String>("listen") {
+            server.listen = listen
+                .map(|l| l.parse())
+                .collect::<std::result::Result<Vec<ListenAddress>, ConfigError>>()?;
+        }
+        if let Some(site_url) = matches.get_one::<String>("site-url") {
+            server.site_url = Some(site_url.clone());
+        }
+
+        let database_url = matches
+            .get_one::<String>("database-url")
+            .cloned()
+            .or(env::var(DATABASE_URL).ok())
+            .or(file.database.url);
+
+        let log_dir = match matches.get_one::<String>("log-dir") {
+            Some(dir) => PathBuf::from(dir),
+            None => match env::var(LOG_DIR) {
+                Ok(dir) => PathBuf::from(dir),
+                Err(_) => match file.logs.dir {
+                    Some(dir) => config_dir.join(dir),
+                    None => get_foil_folder_path().join("log"),
+                },
+            },
+        };
+
+        let backend_path = match env::var(BACKEND_PATH) {
+            Ok(path) => Some(PathBuf::from(path)),
+            Err(_) => file.backend.path.map(|path| config_dir.join(path)),
+        };
+
+        let build_concurrency = match matches.get_one::<usize>("concurrency") {
+            Some(concurrency) => *concurrency,
+            None => match env::var(BUILD_CONCURRENCY) {
+                Ok(v) => v.trim().parse().map_err(|_| {
+                    ConfigError(format!(
+                        "Invalid {} {}, expected a number of builders.",
+                        BUILD_CONCURRENCY, v
+                    ))
+                })?,
+                Err(_) => file
+                    .build
+                    .concurrency
+                    .unwrap_or(available_parallelism().map(|n| n.get()).unwrap_or(1)),
+            },
+        };
+
+        Ok(ProjectConfig {
+            path,
+            database_url,
+            server,
+            backend_path,
+            log_dir,
+            log_max_size: file.logs.max_size_mb.unwrap_or(DEFAULT_LOG_MAX_SIZE_MB) * 1024 * 1024,
+            log_rotate_after: Duration::from_secs(
+                file.logs.rotate_hours.unwrap_or(DEFAULT_LOG_ROTATE_HOURS) * 60 * 60,
+            ),
+            log_retention: Duration::from_secs(
+                file.logs
+                    .retention_days
+                    .unwrap_or(DEFAULT_LOG_RETENTION_DAYS)
+                    * 24
+                    * 60
+                    * 60,
+            ),
+            build_concurrency: build_concurrency.max(1),
+            systemjs_version: file
+                .build
+                .systemjs_version
+                .unwrap_or(DEFAULT_SYSTEMJS_VERSION.to_string()),
+            default_rss: file.posts.rss.unwrap_or(vec!["/blog/*".to_string()]),
+            default_assets: file.posts.assets.unwrap_or(vec!["assets/**/*".to_string()]),
+        })
+    }
+
+    /// The database URL, falling back to a local PostgreSQL database.
+    pub fn database_url(&self) -> String {
+        self.database_url
+            .clone()
+            .unwrap_or(DEFAULT_DATABASE_URL.to_string())
+    }
+}
+
+//=====================================================================================================================
+/// Load the project configuration once at startup, before any command runs.
+pub fn init_config(matches: &ArgMatches) -> Result<()> {
+    let config = ProjectConfig::load(matches)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+/// The project configuration loaded at startup.
+pub fn config() -> &'static ProjectConfig {
+    CONFIG
+        .get()
+        .expect("Foil config must be loaded before running a command.")
+}
+
+//=====================================================================================================================
+/// 📋 Print the effective configuration of the current foil project.
+pub fn show_config() {
+    let config = config();
+    match &config.path {
+        Some(path) => println!("⚙️ Foil config from {}", path.display()),
+        None => println!("⚙️ No foil.toml found, using environment variables and defaults."),
+    }
+    let listen: Vec<String> = config.server.listen.iter().map(|l| l.to_string()).collect();
+    println!(
+        "\n[database]\nurl = {:?}",
+        redact_db_url(&config.database_url())
+    );
+    println!(
+        "\n[server]\nlisten = {:?}\nrenderer_address = {:?}\nrequest_timeout = {}\nrenderer_timeout = {}\nsite_url = {:?}",
+        listen,
+        config.server.renderer_address,
+        config.server.request_timeout.as_secs(),
+        config.server.renderer_timeout.as_secs(),
+        config.server.site_url()
+    );
+    println!(
+        "\n[backend]\npath = {:?}",
+        backend_path().display().to_string()
+    );
+    println!(
+        "\n[build]\nconcurrency = {}\nsystemjs_version = {:?}",
+        config.build_concurrency, config.systemjs_version
+    );
+    println!(
+        "\n[posts]\nrss = {:?}\nassets = {:?}",
+        config.default_rss, config.default_assets
+    );
+    println!(
+        "\n[logs]\ndir = {:?}\nmax_size_mb = {}\nrotate_hours = {}\nretention_days = {}",
+        config.log_dir.display().to_string(),
+        config.log_max_size / 1024 / 1024,
+        config.log_rotate_after.as_secs() / 60 / 60,
+        config.log_retention.as_secs() / 60 / 60 / 24
+    );
+}
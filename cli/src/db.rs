@@ -0,0 +1,268 @@
+use crate::error::{err, Result};
+use crate::misc::connect_db;
+use foil_core::{match_pool, FoilPool};
+use sqlx::migrate::{AppliedMigration, Migrator};
+use std::collections::HashMap;
+
+/// Foil's versioned PostgreSQL migrations, embedded from `cli/migrations/postgres` when foil is compiled.
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// Foil's versioned SQLite migrations, embedded from `cli/migrations/sqlite` when foil is compiled.
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+/// The migrations for whichever database foil is connected to.
+pub fn migrator(pool: &FoilPool) -> &'static Migrator {
+    match pool {
+        FoilPool::Postgres(_) => &POSTGRES_MIGRATOR,
+        FoilPool::Sqlite(_) => &SQLITE_MIGRATOR,
+    }
+}
+
+//=====================================================================================================================
+/// The state of a single migration, comparing the database to the migrations embedded in this binary.
+enum MigrationState {
+    /// Applied to the database, and matching this binary.
+    Applied,
+    /// Known to this binary, but not yet applied to the database.
+    Pending,
+    /// Applied to the database, but with a different checksum than this binary's.
+    Modified,
+    /// Applied to the database, but failed partway through.
+    Dirty,
+    /// Applied to the database by a newer version of foil.
+    Unknown,
+}
+
+/// Every migration's version, description and state, sorted by version.
+async fn migration_states(pool: &FoilPool) -> Result<Vec<(i64, String, MigrationState)>> {
+    let applied: HashMap<i64, (AppliedMigration, bool)> = query_applied_migrations(pool)
+        .await?
+        .into_iter()
+        .map(|(m, success)| (m.version, (m, success)))
+        .collect();
+
+    let migrator = migrator(pool);
+    let mut states = vec![];
+    for migration in migrator
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+    {
+        let state = match applied.get(&migration.version) {
+            None => MigrationState::Pending,
+            Some((_, false)) => MigrationState::Dirty,
+            Some((m, true)) if m.checksum != migration.checksum => MigrationState::Modified,
+            Some(_) => MigrationState::Applied,
+        };
+        states.push((migration.version, migration.description.to_string(), state));
+    }
+    for (version, (_, success)) in applied.iter() {
+        if !migrator.version_exists(*version) {
+            let state = if *success {
+                MigrationState::Unknown
+            } else {
+                MigrationState::Dirty
+            };
+            states.push((*version, "".to_string(), state));
+        }
+    }
+    states.sort_by_key(|(version, _, _)| *version);
+    Ok(states)
+}
+
+/// Every migration recorded in the database and whether it succeeded, without creating the migrations table.
+async fn query_applied_migrations(pool: &FoilPool) -> Result<Vec<(AppliedMigration, bool)>> {
+    let has_migrations_query = match pool {
+        FoilPool::Postgres(_) => "SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = '_sqlx_migrations')",
+        FoilPool::Sqlite(_) => "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    };
+    let (has_migrations_table,): (bool,) =
+        match_pool!(pool, p => sqlx::query_as(has_migrations_query).fetch_one(p).await?);
+    if !has_migrations_table {
+        return Ok(vec![]);
+    }
+    let rows: Vec<(i64, Vec<u8>, bool)> = match_pool!(pool, p => {
+        sqlx::query_as("SELECT version, checksum, success FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(p)
+            .await?
+    });
+    Ok(rows
+        .into_iter()
+        .map(|(version, checksum, success)| {
+            (
+                AppliedMigration {
+                    version,
+                    checksum: checksum.into(),
+                },
+                success,
+            )
+        })
+        .collect())
+}
+
+//=====================================================================================================================
+/// 🐘 Make sure the database schema matches the migrations embedded in this binary before using it.
+pub async fn check_schema(pool: &FoilPool) -> Result<()> {
+    let states = migration_states(pool).await?;
+    if states
+        .iter()
+        .any(|(_, _, s)| matches!(s, MigrationState::Unknown))
+    {
+        println!("❌ The Foil database schema is newer than this version of foil. Update foil, or run `foil db rollback` with the version of foil that migrated it.");
+        return err("Foil database schema is newer than this binary.");
+    }
+    if states
+        .iter()
+        .any(|(_, _, s)| matches!(s, MigrationState::Modified | MigrationState::Dirty))
+    {
+        println!("❌ The Foil database schema doesn't match this version of foil, see `foil db status` for details.");
+        return err("Foil database schema doesn't match this binary.");
+    }
+    if states
+        .iter()
+        .any(|(_, _, s)| matches!(s, MigrationState::Pending))
+    {
+        println!("❌ The Foil database schema is out of date, run `foil db migrate` first.");
+        return err("Foil database schema is out of date.");
+    }
+    Ok(())
+}
+
+//=====================================================================================================================
+/// ⏫ Apply every pending migration to the database.
+pub async fn migrate() -> Result<()> {
+    let pool = connect_db().await?;
+    let pending = migration_states(&pool)
+        .await?
+        .iter()
+        .filter(|(_, _, s)| matches!(s, MigrationState::Pending))
+        .count();
+    if pending == 0 {
+        println!("👍 Foil database schema is already up to date.");
+        return Ok(());
+    }
+    match match_pool!(&pool, p => migrator(&pool).run(p).await) {
+        Ok(()) => {
+            println!("🧑‍🔬 Applied {} migration(s) to the Foil database.", pending);
+            Ok(())
+        }
+        Err(e) => {
+            println!("❌ Failed to migrate the Foil database.");
+            Err(e.into())
+        }
+    }
+}
+
+//=====================================================================================================================
+/// 📋 List every migration and whether it's been applied to the database.
+pub async fn status() -> Result<()> {
+    let pool = connect_db().await?;
+    println!("🐘 Foil database migrations:");
+    for (version, description, state) in migration_states(&pool).await? {
+        let (icon, label) = match state {
+            MigrationState::Applied => ("✅", "applied"),
+            MigrationState::Pending => ("⏳", "pending"),
+            MigrationState::Modified => ("❌", "applied, but modified since"),
+            MigrationState::Dirty => ("❌", "failed partway through"),
+            MigrationState::Unknown => ("❓", "applied by a newer version of foil"),
+        };
+        println!("  {} {} {} ({})", icon, version, description, label);
+    }
+    Ok(())
+}
+
+//=====================================================================================================================
+/// ⏬ Revert applied migrations newer than `target`, or only the latest migration if no target is given.
+pub async fn rollback(target: Option<i64>) -> Result<()> {
+    let pool = connect_db().await?;
+    let applied: Vec<i64> = query_applied_migrations(&pool)
+        .await?
+        .into_iter()
+        .map(|(m, _)| m.version)
+        .collect();
+    let target = match target {
+        Some(v) => v,
+        None => match applied.len() {
+            0 => {
+                println!("👍 No migrations have been applied, nothing to roll back.");
+                return Ok(());
+            }
+            n if n >= 2 => applied[n - 2],
+            _ => 0,
+        },
+    };
+    let reverted = applied.iter().filter(|v| **v > target).count();
+    match match_pool!(&pool, p => migrator(&pool).undo(p, target).await) {
+        Ok(()) => {
+            println!(
+                "🧑‍🔬 Rolled back {} migration(s), the Foil database is now at version {}.",
+                reverted, target
+            );
+            Ok(())
+        }
+        Err(e) => {
+            println!("❌ Failed to roll back the Foil database.");
+            Err(e.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::package_schema::NodeAuthor;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[test]
+    fn migrated_slugs_match_built_slugs() {
+        let names = [
+            "Alain Galvan",
+            "Jane O'Brien",
+            "Seán O'Brien",
+            "  R2-D2 ",
+            "Dr. Jane_Doe, PhD",
+            "ÉLODIE 42",
+        ];
+        async_std::task::block_on(async {
+            // Every connection to an in-memory database is its own database, so keep to one.
+            let pool = SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await
+                .unwrap();
+            let run = |version: i64| {
+                let migration = SQLITE_MIGRATOR
+                    .iter()
+                    .find(|m| m.version == version && m.migration_type.is_up_migration())
+                    .unwrap();
+                sqlx::raw_sql(&migration.sql).execute(&pool)
+            };
+            run(1).await.unwrap();
+            run(2).await.unwrap();
+            for name in names {
+                sqlx::query("INSERT INTO authors (name) VALUES ($1)")
+                    .bind(name)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+            run(3).await.unwrap();
+            let slugs: Vec<(String, String)> =
+                sqlx::query_as("SELECT name, slug FROM authors ORDER BY id")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            for (name, slug) in slugs {
+                let author = NodeAuthor {
+                    name,
+                    slug: None,
+                    email: None,
+                    url: None,
+                    avatar: None,
+                    bio: None,
+                    socials: None,
+                };
+                assert_eq!(slug, author.slug(), "{}", author.name);
+            }
+        });
+    }
+}
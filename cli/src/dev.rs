@@ -0,0 +1,107 @@
+use crate::builder::{build, BuildEvent, BuildMode};
+#[cfg(not(unix))]
+use crate::error::err;
+use crate::error::Result;
+use crate::server::{spawn_server, FoilServer};
+use async_std::channel::{unbounded, Receiver};
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::TcpStream;
+#[cfg(unix)]
+use async_std::os::unix::net::UnixStream;
+use async_std::task::{sleep, spawn};
+use foil_core::ListenAddress;
+use std::time::Duration;
+
+//=====================================================================================================================
+/// 🧑‍💻 Build and watch the current working directory, serve it, and reload the browser after every rebuild.
+pub async fn dev() -> Result<()> {
+    let (events_tx, events_rx) = unbounded();
+    let server_task = spawn(serve_dev(events_rx));
+    build(BuildMode::Development, true, Some(events_tx)).await?;
+    server_task.await;
+    Ok(())
+}
+
+//=====================================================================================================================
+/// Start the server once the initial build finishes, then push reload events to the browser as foils are rebuilt.
+async fn serve_dev(events: Receiver<BuildEvent>) {
+    let mut server: Option<FoilServer> = None;
+    while let Ok(event) = events.recv().await {
+        match event {
+            BuildEvent::Built => match spawn_server(BuildMode::Development, true).await {
+                Ok(s) => {
+                    for address in s.config.listen.iter() {
+                        match address {
+                            ListenAddress::Tcp(addr) => {
+                                println!("🖥️ Foil server running in http://{}", addr)
+                            }
+                            ListenAddress::Unix(_) => {
+                                println!("🖥️ Foil server running in {}", address)
+                            }
+                        }
+                    }
+                    server = Some(s);
+                }
+                Err(e) => println!("❌ Failed to start the Foil server:\n{}", e),
+            },
+            BuildEvent::Rebuilt(permalinks) => {
+                let Some(server) = server.as_mut() else {
+                    continue;
+                };
+                // 🎨 The renderer caches the frontend it was started with, so restart it with the new build.
+                if let Err(e) = server.restart_renderer().await {
+                    println!("❌ Failed to restart the Foil renderer:\n{}", e);
+                    continue;
+                }
+                wait_for_renderer(&server.config.renderer_address).await;
+                match send_reload(&server.config.listen[0], &permalinks).await {
+                    Ok(()) => println!("🔄 Reloading browser."),
+                    Err(e) => println!("❌ Failed to send reload event:\n{}", e),
+                }
+            }
+        }
+    }
+    if let Some(mut server) = server {
+        let _ = server.renderer.kill();
+        let _ = server.backend.kill();
+    }
+}
+
+//=====================================================================================================================
+/// Wait for the server-side renderer to accept connections, so reloaded pages don't hit a renderer that's still starting.
+async fn wait_for_renderer(renderer_address: &str) {
+    for _ in 0..50 {
+        if TcpStream::connect(renderer_address).await.is_ok() {
+            return;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+}
+
+//=====================================================================================================================
+/// 📣 Tell the foil backend which permalinks were rebuilt, which forwards a reload event to every connected browser.
+async fn send_reload(backend_address: &ListenAddress, permalinks: &[String]) -> Result<()> {
+    let body = permalinks.join("\n");
+    let request = format!(
+        "POST /api/v1/dev/reload HTTP/1.1\r\nHost: localhost\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let mut response = String::new();
+    match backend_address {
+        ListenAddress::Tcp(addr) => {
+            let mut stream = TcpStream::connect(addr).await?;
+            stream.write_all(request.as_bytes()).await?;
+            stream.read_to_string(&mut response).await?;
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            let mut stream = UnixStream::connect(path).await?;
+            stream.write_all(request.as_bytes()).await?;
+            stream.read_to_string(&mut response).await?;
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(_) => return err("❌ Unix sockets aren't supported on this platform."),
+    }
+    Ok(())
+}
@@ -1,6 +1,6 @@
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
-pub fn err<'a, T>(msg: &'a str) -> Result<T> {
+pub fn err<T>(msg: &str) -> Result<T> {
     Err(Box::new(std::io::Error::new(
         std::io::ErrorKind::NotFound,
         msg,
@@ -15,7 +15,7 @@ macro_rules! return_err {
             Ok(v) => v,
             Err(e) => {
                 println!("{:?}", e);
-                return crate::error::err($b);
+                return $crate::error::err($b);
             }
         }
     };
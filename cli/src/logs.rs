@@ -0,0 +1,332 @@
+use crate::config::config;
+use crate::error::{err, Result};
+use async_std::task::sleep;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{stdout, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The server processes whose output is logged, and which `foil logs` reads.
+pub const LOG_PROCESSES: [&str; 2] = ["backend", "renderer"];
+
+/// Rotated logs are named with when they were rotated, in a format that sorts chronologically.
+const ARCHIVE_DATE_FORMAT: &str = "%Y-%m-%d_%H-%M-%S_%3f";
+
+/// How often `foil logs --follow` checks for new lines.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A log shared by every restart of the same server process.
+pub type SharedLog = Arc<Mutex<RotatingLog>>;
+
+//=====================================================================================================================
+/// 🧻 The log of a server process, rotated once it grows too large or too old.
+/// The current log is `foil-<process>.log`, rotated logs are `foil-<process>-<date>.log`.
+pub struct RotatingLog {
+    process_name: String,
+    file: File,
+    size: u64,
+    opened_at: SystemTime,
+}
+
+impl RotatingLog {
+    /// Open the current log of a server process, appending to it.
+    pub fn open(process_name: &str) -> Result<RotatingLog> {
+        let file = open_append(&current_log_path(process_name))?;
+        let metadata = file.metadata()?;
+        let opened_at = metadata
+            .created()
+            .or(metadata.modified())
+            .unwrap_or(SystemTime::now());
+        Ok(RotatingLog {
+            process_name: process_name.to_string(),
+            file,
+            size: metadata.len(),
+            opened_at,
+        })
+    }
+
+    /// Open the current log of a server process, to be shared by every restart of it.
+    pub fn shared(process_name: &str) -> Result<SharedLog> {
+        Ok(Arc::new(Mutex::new(RotatingLog::open(process_name)?)))
+    }
+
+    /// Write a line of the process' output, prefixed with when it was written.
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        if self.should_rotate() {
+            if let Err(e) = self.rotate() {
+                println!(
+                    "❌ Failed to rotate the Foil {} log:\n{}",
+                    self.process_name, e
+                );
+            }
+        }
+        let line = format!(
+            "{} {}\n",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            line
+        );
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn should_rotate(&self) -> bool {
+        let config = config();
+        if self.size == 0 {
+            return false;
+        }
+        let too_large = config.log_max_size > 0 && self.size >= config.log_max_size;
+        let too_old = !config.log_rotate_after.is_zero()
+            && self.opened_at.elapsed().unwrap_or_default() >= config.log_rotate_after;
+        too_large || too_old
+    }
+
+    /// Move the current log aside and start a new one, deleting rotated logs past their retention.
+    fn rotate(&mut self) -> Result<()> {
+        let path = current_log_path(&self.process_name);
+        fs::rename(&path, archive_log_path(&self.process_name))?;
+        self.file = open_append(&path)?;
+        self.size = 0;
+        self.opened_at = SystemTime::now();
+        prune_logs();
+        Ok(())
+    }
+}
+
+//=====================================================================================================================
+/// 🚰 Copy a process' output into its log line by line, until the process exits.
+pub fn pipe_log<R: Read + Send + 'static>(output: R, log: SharedLog) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let text = String::from_utf8_lossy(&line);
+            if let Ok(mut log) = log.lock() {
+                let _ = log.write_line(text.trim_end_matches(['\r', '\n']));
+            }
+        }
+    });
+}
+
+/// The current log of a server process.
+pub fn current_log_path(process_name: &str) -> PathBuf {
+    config().log_dir.join(format!("foil-{}.log", process_name))
+}
+
+/// A new rotated log of a server process, named with the current date.
+pub fn archive_log_path(process_name: &str) -> PathBuf {
+    config().log_dir.join(format!(
+        "foil-{}-{}.log",
+        process_name,
+        Utc::now().format(ARCHIVE_DATE_FORMAT)
+    ))
+}
+
+fn open_append(path: &PathBuf) -> Result<File> {
+    Ok(OpenOptions::new().create(true).append(true).open(path)?)
+}
+
+//=====================================================================================================================
+/// 🧹 Delete rotated logs past their retention, along with the per-start logs of older foil versions.
+pub fn prune_logs() {
+    let config = config();
+    if config.log_retention.is_zero() {
+        return;
+    }
+    let current: Vec<String> = LOG_PROCESSES
+        .iter()
+        .map(|p| format!("foil-{}.log", p))
+        .collect();
+    let Ok(entries) = fs::read_dir(&config.log_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let rotated =
+            name.starts_with("foil-") && name.ends_with(".log") && !current.contains(&name);
+        let legacy = name.starts_with("foil-") && name.contains("-log-") && name.ends_with(".txt");
+        if !rotated && !legacy {
+            continue;
+        }
+        let expired = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .map(|modified| modified.elapsed().unwrap_or_default() >= config.log_retention)
+            .unwrap_or(false);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+//=====================================================================================================================
+/// Every log of a server process from oldest to newest, the current log last.
+fn log_files(process_name: &str) -> Vec<PathBuf> {
+    let prefix = format!("foil-{}-", process_name);
+    let mut files: Vec<PathBuf> = fs::read_dir(&config().log_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    name.starts_with(&prefix) && name.ends_with(".log")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.push(current_log_path(process_name));
+    files
+}
+
+/// Split the complete lines off of a log's contents, returning how many bytes they took.
+fn complete_lines(bytes: &[u8]) -> (Vec<String>, u64) {
+    let end = match bytes.iter().rposition(|b| *b == b'\n') {
+        Some(end) => end + 1,
+        None => return (vec![], 0),
+    };
+    let lines = String::from_utf8_lossy(&bytes[..end])
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    (lines, end as u64)
+}
+
+/// When a log line was written.
+fn line_date(line: &str) -> Option<DateTime<Utc>> {
+    let (date, _) = line.split_once(' ')?;
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+//=====================================================================================================================
+/// 📜 Print the logs of the given server processes, optionally only since a given date,
+/// then with `follow`, keep printing new lines as they're written.
+pub async fn show_logs(
+    processes: &[&str],
+    follow: bool,
+    since: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let prefixed = processes.len() > 1;
+    // 🚰 Stop quietly once stdout is closed, such as when piped into `head`.
+    let print_line = |process: &str, line: &str| {
+        if prefixed {
+            writeln!(stdout(), "{:<8} | {}", process, line)
+        } else {
+            writeln!(stdout(), "{}", line)
+        }
+    };
+
+    // 📚 Every line since the given date, in the order they were written across processes.
+    let mut lines: Vec<(Option<DateTime<Utc>>, &str, String)> = vec![];
+    let mut offsets: Vec<u64> = vec![];
+    for process in processes {
+        let mut offset = 0;
+        for path in log_files(process) {
+            let bytes = fs::read(&path).unwrap_or_default();
+            let (file_lines, consumed) = complete_lines(&bytes);
+            offset = consumed;
+            // Multiline output, such as stack traces, belongs to the line before it.
+            let mut date = None;
+            for line in file_lines {
+                date = line_date(&line).or(date);
+                let after_since = match (since, date) {
+                    (Some(since), Some(date)) => date >= since,
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                };
+                if after_since {
+                    lines.push((date, process, line));
+                }
+            }
+        }
+        offsets.push(offset);
+    }
+    if prefixed {
+        lines.sort_by_key(|(date, _, _)| *date);
+    }
+    for (_, process, line) in lines.iter() {
+        if print_line(process, line).is_err() {
+            return Ok(());
+        }
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    // 👁️ Keep reading the current logs, starting over whenever one is rotated.
+    loop {
+        sleep(FOLLOW_INTERVAL).await;
+        for (process, offset) in processes.iter().zip(offsets.iter_mut()) {
+            let path = current_log_path(process);
+            let len = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if len < *offset {
+                *offset = 0;
+            }
+            if len == *offset {
+                continue;
+            }
+            let mut bytes = vec![];
+            let read = File::open(&path).and_then(|mut file| {
+                file.seek(SeekFrom::Start(*offset))?;
+                file.read_to_end(&mut bytes)
+            });
+            if read.is_err() {
+                continue;
+            }
+            let (new_lines, consumed) = complete_lines(&bytes);
+            *offset += consumed;
+            for line in new_lines {
+                if print_line(process, &line).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+//=====================================================================================================================
+/// ⏱️ Parse when to show logs since, either how long ago such as `30s`, `15m`, `2h` or `7d`,
+/// or a UTC date such as `2024-05-01`, `2024-05-01 12:30:00` or `2024-05-01T12:30:00Z`.
+pub fn parse_since(since: &str) -> Result<DateTime<Utc>> {
+    let since = since.trim();
+    if let Some(unit) = since.chars().last() {
+        let seconds = match unit {
+            's' => Some(1),
+            'm' => Some(60),
+            'h' => Some(60 * 60),
+            'd' => Some(24 * 60 * 60),
+            _ => None,
+        };
+        if let (Some(seconds), Ok(amount)) = (seconds, since[..since.len() - 1].parse::<i64>()) {
+            return Ok(Utc::now() - chrono::Duration::seconds(amount * seconds));
+        }
+    }
+    if let Ok(date) = DateTime::parse_from_rfc3339(since) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(since, "%Y-%m-%d %H:%M:%S") {
+        return Ok(date.and_utc());
+    }
+    if let Some(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+    {
+        return Ok(date.and_utc());
+    }
+    err(&format!(
+        "❌ Invalid --since {}, expected how long ago such as 15m, 2h or 7d, or a date such as 2024-05-01 12:30:00.",
+        since
+    ))
+}
@@ -2,18 +2,27 @@
 #![warn(unused_crate_dependencies)]
 
 mod builder;
+mod config;
+mod db;
+mod dev;
 mod error;
+mod logs;
 mod misc;
 mod query_post;
 mod reset;
 mod server;
+mod server_state;
+mod supervisor;
 
 use builder::{build, BuildMode};
 use chrono::Utc;
-use clap::{arg, ArgMatches, Command};
+use clap::{arg, ArgAction, ArgMatches, Command};
+use config::{init_config, show_config};
+use dev::dev;
 use lazy_static::lazy_static;
+use logs::{parse_since, show_logs, LOG_PROCESSES};
 use reset::reset;
-use server::start_server;
+use server::{restart_server, server_status, start_server, stop_server};
 use std::io::{stdout, Write};
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
@@ -32,6 +41,18 @@ fn get_build_mode(default: BuildMode, sub_m: &ArgMatches) -> BuildMode {
     default
 }
 
+//=====================================================================================================================
+/// ❌ Unwrap the result of a command, or print its error and exit with a failure status.
+fn exit_on_error<T>(result: Result<T>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 lazy_static! {
     static ref BUILD_DATE: String = Utc::now().format("%m/%d/%Y").to_string();
 }
@@ -50,6 +71,16 @@ async fn main() -> Result<()> {
     let mut app = Command::new("✨ foil")
         .version("0.1.0")
         .about("💫 Foil's primary CLI application, provides everything needed to start and manage a foil project.")
+        .args(&[arg!(--config <FILE> "⚙️ Use this foil.toml instead of the closest one to the current directory.").global(true),
+                arg!(--"database-url" <URL> "📚 Override the database URL.").global(true),
+                arg!(--"site-url" <URL> "🌐 Override the public base URL of the site.").global(true),
+                arg!(--listen <ADDRESS> "👂 Override the addresses the backend listens on, such as 127.0.0.1:4017 or unix:/run/foil.sock.")
+                    .global(true)
+                    .action(ArgAction::Append),
+                arg!(--"log-dir" <DIR> "🧻 Override the folder server logs are written to.").global(true),
+                arg!(--concurrency <N> "🧵 Override how many foil builders may run at once.")
+                    .global(true)
+                    .value_parser(clap::value_parser!(usize))])
         .subcommand(
             Command::new("build")
                 .display_order(3)
@@ -59,13 +90,63 @@ async fn main() -> Result<()> {
                         arg!(--watch "👁️ Build your foil project and automatically compile any changes to it.")])
         )
         .subcommand(
-            Command::new("server")
+            Command::new("dev")
                 .display_order(4)
+                .about("🧑‍💻 Build your foil project in Development mode, serve it, and reload the browser whenever it changes.")
+        )
+        .subcommand(
+            Command::new("db")
+                .display_order(6)
+                .about("🐘 Manage the Foil database schema.")
+                .subcommand(
+                    Command::new("migrate")
+                    .about("Apply every pending migration to the database."))
+                .subcommand(
+                    Command::new("status")
+                    .about("List every migration and whether it's been applied."))
+                .subcommand(
+                    Command::new("rollback")
+                    .about("Revert the latest migration, or every migration newer than --to.")
+                    .arg(arg!(--to <VERSION> "The version to roll back to, 0 reverts everything.")
+                        .value_parser(clap::value_parser!(i64))))
+        )
+        .subcommand(
+            Command::new("config")
+                .display_order(7)
+                .about("⚙️ Inspect the Foil project configuration.")
+                .subcommand(
+                    Command::new("show")
+                    .about("Print the effective configuration, from foil.toml, environment variables and flags."))
+        )
+        .subcommand(
+            Command::new("logs")
+                .display_order(8)
+                .about("📜 Print the Foil server logs.")
+                .args(&[arg!([PROCESS] "The server process to print the logs of, every process if not given.")
+                            .value_parser(LOG_PROCESSES),
+                        arg!(-f --follow "👁️ Keep printing new log lines as they're written."),
+                        arg!(--since <TIME> "⏱️ Only print lines since how long ago, such as 15m, 2h or 7d, or since a UTC date such as 2024-05-01 12:30:00.")])
+        )
+        .subcommand(
+            Command::new("server")
+                .display_order(5)
                 .about("🖥️ Manage the Foil server.")
                 .subcommand(
                     Command::new("start")
                         .about("Start the foil server.")
                         .arg(arg!(--release "🧑‍💼 Runs server in Release mode. (default)"))
+                        .arg(arg!(--dev "🧑‍💻 Runs server in Development mode."))
+                        .arg(arg!(-d --detach "👻 Runs server in the background.")))
+                .subcommand(
+                    Command::new("stop")
+                    .about("Stop the foil server."))
+                .subcommand(
+                    Command::new("status")
+                    .about("Show the foil server's processes, addresses, log files and database status."))
+                .subcommand(
+                    Command::new("restart")
+                        .about("Restart the foil server in the background.")
+                        .arg(arg!(--release "🧑‍💼 Runs server in Release mode. (default)"))
                         .arg(arg!(--dev "🧑‍💻 Runs server in Development mode.")))
                 .subcommand(
                     Command::new("reset")
@@ -79,19 +160,69 @@ async fn main() -> Result<()> {
         .expect("failed to write to stdout");
 
     let matches = app.get_matches();
+    if let Err(e) = init_config(&matches) {
+        println!("❌ Failed to load foil config:\n{}", e);
+        std::process::exit(1);
+    }
     match matches.subcommand() {
         Some(("build", sub_m)) => {
             let build_mode = get_build_mode(BuildMode::Release, sub_m);
-            let _ = build(build_mode.clone()).await;
+            let watch = *sub_m.get_one::<bool>("watch").unwrap_or(&false);
+            exit_on_error(build(build_mode.clone(), watch, None).await);
+        }
+        Some(("dev", _sub_m)) => {
+            exit_on_error(dev().await);
+        }
+        Some(("db", sub_m)) => {
+            match sub_m.subcommand() {
+                Some(("migrate", _sub_m)) => {
+                    exit_on_error(db::migrate().await);
+                }
+                Some(("status", _sub_m)) => {
+                    exit_on_error(db::status().await);
+                }
+                Some(("rollback", sub_m)) => {
+                    let target = sub_m.get_one::<i64>("to").copied();
+                    exit_on_error(db::rollback(target).await);
+                }
+                _ => (),
+            };
+        }
+        Some(("config", sub_m)) => {
+            if let Some(("show", _sub_m)) = sub_m.subcommand() {
+                show_config();
+            }
+        }
+        Some(("logs", sub_m)) => {
+            let processes: Vec<&str> = match sub_m.get_one::<String>("PROCESS") {
+                Some(process) => vec![process.as_str()],
+                None => LOG_PROCESSES.to_vec(),
+            };
+            let follow = *sub_m.get_one::<bool>("follow").unwrap_or(&false);
+            let since = sub_m
+                .get_one::<String>("since")
+                .map(|s| exit_on_error(parse_since(s)));
+            exit_on_error(show_logs(&processes, follow, since).await);
         }
         Some(("server", sub_m)) => {
             match sub_m.subcommand() {
                 Some(("start", sub_m)) => {
                     let build_mode = get_build_mode(BuildMode::Release, sub_m);
-                    let _ = start_server(build_mode.clone()).await;
+                    let detach = *sub_m.get_one::<bool>("detach").unwrap_or(&false);
+                    exit_on_error(start_server(build_mode.clone(), detach).await);
+                }
+                Some(("stop", _sub_m)) => {
+                    exit_on_error(stop_server().await);
+                }
+                Some(("status", _sub_m)) => {
+                    exit_on_error(server_status().await);
+                }
+                Some(("restart", sub_m)) => {
+                    let build_mode = get_build_mode(BuildMode::Release, sub_m);
+                    exit_on_error(restart_server(build_mode).await);
                 }
                 Some(("reset", _sub_m)) => {
-                    let _ = reset().await;
+                    exit_on_error(reset().await);
                 }
                 _ => (),
             };
@@ -1,39 +1,95 @@
-use crate::error::Result;
+use crate::builder::build_mode::BuildMode;
+use crate::config::config;
+use crate::error::{err, Result};
+use foil_core::pool::is_sqlite_url;
+use foil_core::{redact_credentials, redact_db_url, FoilPool};
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Pool, Postgres};
-use std::env;
+use std::str::FromStr;
 
 /// The Foil database URL environment variable name.
-pub const DATABASE_URL: &'static str = "FOIL_DATABASE_URL";
+pub const DATABASE_URL: &str = "FOIL_DATABASE_URL";
 
-/// 🌐 Get the database URL from the runtime environment variable.
+/// The environment variable that opts into the foil builder's Node.js import resolver.
+pub const NODE_IMPORT_RESOLVER: &str = "FOIL_NODE_RESOLVER";
+
+/// The environment variable that enables live reloads in the Foil backend.
+pub const LIVE_RELOAD: &str = "FOIL_LIVE_RELOAD";
+
+/// 🌐 Make sure release builds know the public base URL of the site, since their feeds and sitemaps publish it.
+/// Development builds default to `http://localhost:4017`.
+pub fn check_site_url(build_mode: &BuildMode) -> Result<()> {
+    if *build_mode == BuildMode::Release && config().server.site_url.is_none() {
+        return err("❌ Release builds need the site's public URL for their feeds and sitemaps. Set `site_url` in the [server] section of foil.toml, FOIL_SITE_URL or --site-url.");
+    }
+    Ok(())
+}
+
+/// 🌐 Get the public base URL of the site from the foil config, without a trailing slash.
+pub fn get_site_url() -> String {
+    let server = &config().server;
+    if server.site_url.is_none() {
+        println!("Couldn't find Foil site URL in foil.toml or environment variable FOIL_SITE_URL.\nDefaulting to http://localhost:4017");
+    }
+    server.site_url()
+}
+
+/// 🌐 Get the database URL from the foil config.
 pub fn get_db_url() -> String {
-    let db_url = match env::var(DATABASE_URL) {
-        Ok(val) => val,
-        Err(_e) => {
-            println!("Couldn't find Foil database URL environment variable FOIL_DATABASE_URL.\nDefaulting to postgres://localhost/foil");
-            "postgres://localhost/foil".to_string()
-        }
-    };
-    db_url
+    let config = config();
+    if config.database_url.is_none() {
+        println!("Couldn't find Foil database URL in foil.toml or environment variable FOIL_DATABASE_URL.\nDefaulting to postgres://localhost/foil");
+    }
+    config.database_url()
 }
 
-/// 📚 Connect to the database for Foil.
-pub async fn connect_db() -> Result<Pool<Postgres>> {
+//=====================================================================================================================
+/// 📚 Connect to the database for Foil, picking PostgreSQL or SQLite by the URL's scheme.
+pub async fn connect_db() -> Result<FoilPool> {
     // 📚 Configure Database:
     let db_url = get_db_url();
-    let db_url_public_split: Vec<&str> = db_url.split("?").collect();
-    let db_url_public = if db_url_public_split.len() >= 2 {
-        db_url_public_split[0]
-    } else {
-        &db_url
-    };
+    // 🙈 Never print the database password, even in errors.
+    let db_url_public = redact_db_url(&db_url);
+    if is_sqlite_url(&db_url) {
+        println!("🪶 Opening SQLite database in: {}", db_url_public);
+        // SQLite has no regex operator, so register one for permalink globs.
+        let options = match SqliteConnectOptions::from_str(&db_url) {
+            Ok(options) => options.create_if_missing(true).with_regexp(),
+            Err(e) => return err(&redact_credentials(&e.to_string())),
+        };
+        return match Pool::connect_with(options).await {
+            Ok(pool) => Ok(FoilPool::Sqlite(pool)),
+            Err(e) => {
+                println!("Failed to open SQLite database, does its folder exist?");
+                err(&redact_credentials(&e.to_string()))
+            }
+        };
+    }
     println!("🐘 Opening PostgreSQL connection in: {}", db_url_public);
     let postgres_pool: Pool<Postgres> = match Pool::connect(&db_url).await {
         Ok(pool) => pool,
         Err(e) => {
             println!("Failed to connect to database, is PostgreSQL running?");
-            return Err(e.into());
+            return err(&redact_credentials(&e.to_string()));
         }
     };
-    return Ok(postgres_pool);
+    Ok(FoilPool::Postgres(postgres_pool))
+}
+
+//=====================================================================================================================
+/// 🧪 A new empty folder for a test to write files to.
+#[cfg(test)]
+pub fn test_folder(name: &str) -> std::path::PathBuf {
+    let folder = std::env::temp_dir().join(format!("foil-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&folder);
+    std::fs::create_dir_all(&folder).unwrap();
+    folder
+}
+
+/// 🧪 Write a file for a test, creating its folders.
+#[cfg(test)]
+pub fn write_test_file(folder: &std::path::Path, path: &str, contents: &str) {
+    let path = folder.join(path);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
 }
@@ -1,33 +1,33 @@
-use crate::error::Result;
+use crate::db::migrator;
+use crate::error::{err, Result};
 use crate::misc::connect_db;
-use sqlx::migrate::Migrator;
-use std::path::Path;
+use foil_core::match_pool;
 pub async fn reset() -> Result<()> {
     // 📚 Configure Database:
     let postgres_pool = connect_db().await?;
 
-    // Begin clear, relies on SQLX migrations:
+    // Begin clear, dropping every foil table along with its migration history:
     println!("🧪 Clearing Foil database...");
-    let m = Migrator::new(Path::new("./migrations")).await?;
-    match m.run(&postgres_pool).await {
-        Ok(()) => {
-            println!("🧑‍🔬 Reset foil database successfully.");
-        }
-        Err(e) => {
-            println!("🫗 Reset database with errors.");
-            println!("{:?}", e)
-        }
-    }
-
-    match sqlx::query("DROP TABLE _sqlx_migrations")
-        .execute(&postgres_pool)
-        .await
+    let drop_tables = if postgres_pool.is_sqlite() {
+        include_str!("sql/sqlite/drop_tables.sql")
+    } else {
+        include_str!("sql/drop_tables.sql")
+    };
+    match match_pool!(&postgres_pool, p => sqlx::raw_sql(drop_tables).execute(p).await.map(|_| ()))
     {
         Ok(_) => {}
         Err(e) => {
-            println!("Dropped migrations failed:");
+            println!("Dropping tables failed:");
             println!("{:?}", e)
         }
     };
+
+    // Then recreate them from foil's embedded migrations:
+    match match_pool!(&postgres_pool, p => migrator(&postgres_pool).run(p).await) {
+        Ok(()) => {
+            println!("🧑‍🔬 Reset foil database successfully.");
+        }
+        Err(e) => return err(&format!("🫗 Reset database with errors.\n{:?}", e)),
+    }
     Ok(())
 }
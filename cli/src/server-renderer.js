@@ -81,6 +81,7 @@ const foilRequestHandler = async (request, response) => {
     reactDom.hydrateRoot(document, node);
   }
   main();
+  ${liveReload ? `new EventSource("/api/v1/dev/events").addEventListener("reload", () => location.reload());` : ""}
 `;
     const { pipe } = renderToPipeableStream(node, {
         bootstrapScriptContent,
@@ -92,6 +93,14 @@ const foilRequestHandler = async (request, response) => {
 };
 
 // ====================================================================================================================
+// 👂 Listen on the renderer address the foil backend proxies pages to, such as 127.0.0.1:4011 or [::1]:4011.
+const rendererAddress = process.env.FOIL_RENDERER_ADDRESS || "127.0.0.1:4011";
+const portSeparator = rendererAddress.lastIndexOf(":");
+const rendererHost = rendererAddress.slice(0, portSeparator).replace(/^\[(.*)\]$/, "$1");
+const rendererPort = Number(rendererAddress.slice(portSeparator + 1));
 const server = createServer(foilRequestHandler);
-server.listen(4011, "127.0.0.1", () => { });
+server.listen(rendererPort, rendererHost, () => { });
+
+// 🛑 Finish in-flight requests before exiting when `foil server` shuts down.
+process.on("SIGTERM", () => server.close(() => process.exit(0)));
 
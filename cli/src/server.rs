@@ -1,151 +1,627 @@
+use crate::builder::get_foil_builder_path;
+use crate::builder::get_foil_folder_path;
 use crate::builder::BuildMode;
-use crate::builder::{get_foil_builder_path, get_foil_folder_path};
-use crate::error::Result;
+use crate::config::{config, BACKEND_PATH, BUILD_CONCURRENCY, LOG_DIR};
+use crate::db::check_schema;
+use crate::error::{err, Result};
+use crate::logs::{
+    archive_log_path, current_log_path, pipe_log, prune_logs, RotatingLog, SharedLog,
+};
 use crate::misc::connect_db;
-use crate::misc::{get_db_url, DATABASE_URL};
+use crate::misc::{get_db_url, DATABASE_URL, LIVE_RELOAD};
 use crate::query_post::query_post;
+use crate::server_state::{is_process_running, signal_process, ServerLock, ServerState};
+use crate::supervisor::supervise;
+use async_std::future::timeout;
+use async_std::task::sleep;
 use chrono::{DateTime, Utc};
-use lexiclean::Lexiclean;
-use path_slash::PathBufExt;
+use foil_core::config::CONFIG_PATH;
+use foil_core::{clean_path_string, FoilPool, ServerConfig};
 use std::env;
 use std::fs;
-use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
 use std::path::PathBuf;
 use std::process;
 use std::process::Stdio;
+use std::time::{Duration, Instant};
 
-fn clean_path_string(p: &std::path::PathBuf) -> String {
-    p.clone()
-        .lexiclean()
-        .to_slash()
-        .unwrap()
-        .to_string()
-        .replace("\\", "/")
-}
+/// The environment variable with the log file a detached foil server writes its own output to.
+const SERVER_LOG: &str = "FOIL_SERVER_LOG";
+
+/// How long a detached foil server may take to start before the foil CLI stops waiting for it.
+const DETACH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a foil server has to shut down gracefully before it's killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long the foil backend may take to print its version before it's killed.
+const VERSION_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `foil server status` waits on the database.
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
 
 //=====================================================================================================================
 // NPM is somewhat buggy at times, and requires the extension on windows.
 #[cfg(windows)]
-const FOIL_BACKEND: &'static str = "foil_backend.exe";
+const FOIL_BACKEND: &str = "foil_backend.exe";
 
 #[cfg(not(windows))]
-const FOIL_BACKEND: &'static str = "foil_backend";
+const FOIL_BACKEND: &str = "foil_backend";
 
-pub async fn start_server(_build_mode: BuildMode) -> Result<()> {
-    // 📚 Configure database...
-    let pool = connect_db().await?;
-    let cwd = env::current_dir().unwrap_or_default();
+//=====================================================================================================================
+/// The foil backend executable, from the config if set, otherwise next to the foil CLI.
+pub fn backend_path() -> PathBuf {
+    match &config().backend_path {
+        Some(path) => path.clone(),
+        None => get_foil_folder_path().join(FOIL_BACKEND),
+    }
+}
 
-    // 📦 Resolve Foil package.json from current working directory, attempt to run server from it:
-    match query_post(&pool, "/".to_string()).await {
-        Ok(post) => {
-            // A few common vars:
-            let foil_folder_path = get_foil_folder_path();
-            let foil_builder_path = get_foil_builder_path();
-            let foil_cache_path = foil_builder_path.join(PathBuf::from("cache"));
-            let foil_log_path = foil_folder_path.join(PathBuf::from("log"));
-
-            fs::create_dir_all(&foil_cache_path)?;
-            fs::create_dir_all(&foil_log_path)?;
-
-            // 🧻 Create log file for backend errors:
-            let dt = Utc::now();
-            let naive_utc = dt.naive_utc();
-            let offset = dt.offset().clone();
-            let date_now = DateTime::<Utc>::from_naive_utc_and_offset(naive_utc, offset);
-            let date_now_string = date_now
-                .to_string()
-                .replace(":", "_")
-                .replace(" ", "_")
-                .replace(".", "_");
-            let backend_log_file = format!("foil-backend-log-{}.txt", &date_now_string);
-            let backend_log_file_abs = foil_log_path.clone().join(&backend_log_file);
-            let backend_file = File::create(backend_log_file_abs).unwrap();
-            let backend_stdio = Stdio::from(backend_file);
-
-            // 🌐 Spawn child processes for the server:
-            let foil_database_url = get_db_url();
-            let mut backend_server_child = process::Command::new(&FOIL_BACKEND)
-                .current_dir(&cwd)
-                .stdin(process::Stdio::null())
-                .env_remove("args")
-                .env(DATABASE_URL, foil_database_url)
-                .env("RUST_LOG", env::var("RUST_LOG").unwrap_or_default())
-                .stderr(backend_stdio)
-                .spawn()
-                .unwrap();
-
-            // 🎨 The Foil server-side renderer currently exists as a separate process:
-            let frontend_main = post.name.clone();
-            let main_path = clean_path_string(&PathBuf::from(&post.output_path).join("main.js"));
-            let mut import_map_str =
-                format!("        \"{}\": \"file:///{}\",\n", &post.name, main_path);
-            for public_module in post.public_modules {
-                let mod_path = clean_path_string(
-                    &PathBuf::from(&post.output_path).join(public_module.clone() + ".js"),
-                );
-                import_map_str += &format!(
-                    "        \"{}\": \"file:///{}\",\n",
-                    &public_module, mod_path
-                );
+/// 🔎 Find the foil backend, making sure it's the same version as the foil CLI.
+async fn find_backend() -> Result<PathBuf> {
+    let path = backend_path();
+    if !path.is_file() {
+        return err(&format!(
+            "❌ Couldn't find the Foil backend at {}.\nBuild it with `cargo build --release` in foil's backend folder and copy {} next to the Foil CLI, or set its path with [backend] path in foil.toml or {}.",
+            path.display(),
+            FOIL_BACKEND,
+            BACKEND_PATH
+        ));
+    }
+
+    // An older backend without --version would start serving instead, so without a database it exits right away.
+    let child = process::Command::new(&path)
+        .arg("--version")
+        .env_remove(DATABASE_URL)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            return err(&format!(
+                "❌ Failed to run the Foil backend at {}:\n{}",
+                path.display(),
+                e
+            ))
+        }
+    };
+    let deadline = Instant::now() + VERSION_CHECK_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => sleep(Duration::from_millis(50)).await,
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return err(&format!(
+                    "❌ The Foil backend at {} didn't report its version within {} seconds.\nInstall the backend from the same release as the CLI, or set the path of a matching one with [backend] path in foil.toml or {}.",
+                    path.display(),
+                    VERSION_CHECK_TIMEOUT.as_secs(),
+                    BACKEND_PATH
+                ));
             }
-            let server_src = format!(
-                include_str!("server-renderer-template.txt"),
-                frontend_main,
-                import_map_str,
-                include_str!("server-renderer.js")
-            );
+            Err(e) => {
+                let _ = child.kill();
+                return err(&format!(
+                    "❌ Failed to run the Foil backend at {}:\n{}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
+    let mut stdout = String::new();
+    if let Some(mut pipe) = child.stdout.take() {
+        let _ = pipe.read_to_string(&mut stdout);
+    }
+    let cli_version = env!("CARGO_PKG_VERSION");
+    // Anything without a `foil_backend <version>` line is treated as a mismatch.
+    let backend_version = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("foil_backend "))
+        .map(str::trim);
+    if backend_version != Some(cli_version) {
+        let backend_version = match backend_version {
+            Some(backend_version) => format!("v{}", backend_version),
+            None => "an unknown version".to_string(),
+        };
+        return err(&format!(
+            "❌ The Foil backend at {} is {}, but the Foil CLI is v{}.\nInstall the backend from the same release as the CLI, or set the path of a matching one with [backend] path in foil.toml or {}.",
+            path.display(),
+            backend_version,
+            cli_version,
+            BACKEND_PATH
+        ));
+    }
+    Ok(path)
+}
 
-            // Due to the idiosyncrasies of Node.js and TypeScript, we must generate and load a renderer file:
-            // Also, due to the way node resolves modules, it must exist next to the frontend.
-            // We may want to clear this cache path in the future...
+//=====================================================================================================================
+/// 🖥️ A running foil backend and server-side renderer.
+pub struct FoilServer {
+    /// The foil backend process, serving the API and static assets.
+    pub backend: process::Child,
+    /// The node.js server-side renderer process.
+    pub renderer: process::Child,
+    /// The listen addresses, renderer address and timeouts both processes were started with.
+    pub config: ServerConfig,
+    /// When this server was started.
+    pub started_at: DateTime<Utc>,
+    backend_path: PathBuf,
+    pool: FoilPool,
+    build_mode: BuildMode,
+    live_reload: bool,
+    backend_log: SharedLog,
+    renderer_log: SharedLog,
+}
+
+impl FoilServer {
+    /// Restart the server-side renderer so it picks up a newly built frontend.
+    pub async fn restart_renderer(&mut self) -> Result<()> {
+        let _ = self.renderer.kill();
+        let _ = self.renderer.wait();
+        self.renderer = spawn_renderer(
+            &self.pool,
+            &self.config,
+            &self.build_mode,
+            self.live_reload,
+            &self.renderer_log,
+        )
+        .await?;
+        Ok(())
+    }
 
-            let server_source_file = frontend_main + "-renderer.generated.mjs";
-            let server_source_file_abs = foil_cache_path.clone().join(&server_source_file);
-            let server_source_file_abs_str = clean_path_string(&server_source_file_abs);
+    /// Restart the foil backend, such as after it crashed.
+    pub fn restart_backend(&mut self) -> Result<()> {
+        let _ = self.backend.kill();
+        let _ = self.backend.wait();
+        self.backend = spawn_backend(
+            &self.backend_path,
+            &self.config,
+            self.live_reload,
+            &self.backend_log,
+        )?;
+        Ok(())
+    }
 
-            let write_result = fs::write(&server_source_file_abs, server_src);
-            if write_result.is_err() {
+    /// The log file of the foil backend.
+    pub fn backend_log_path() -> PathBuf {
+        current_log_path("backend")
+    }
+
+    /// The log file of the server-side renderer.
+    pub fn renderer_log_path() -> PathBuf {
+        current_log_path("renderer")
+    }
+
+    /// The state file describing this server, for `foil server status` and `foil server stop`.
+    pub fn state(&self) -> ServerState {
+        ServerState {
+            supervisor_pid: process::id(),
+            backend_pid: self.backend.id(),
+            renderer_pid: self.renderer.id(),
+            started_at: self.started_at,
+            cwd: env::current_dir().unwrap_or_default(),
+            listen: self.config.listen.iter().map(|l| l.to_string()).collect(),
+            renderer_address: self.config.renderer_address.clone(),
+            server_log: env::var(SERVER_LOG).ok().map(PathBuf::from),
+            backend_log: Self::backend_log_path(),
+            renderer_log: Self::renderer_log_path(),
+        }
+    }
+}
+
+//=====================================================================================================================
+/// Start the foil server, keeping it running until it's told to stop. With `detach`, it keeps running in the background.
+pub async fn start_server(build_mode: BuildMode, detach: bool) -> Result<()> {
+    // ☝️ Only one foil server may run at a time.
+    if let Some(state) = ServerState::read() {
+        if state.is_running() {
+            return err(&format!(
+                "❌ A Foil server is already running with PID {}, stop it with `foil server stop`.",
+                state.supervisor_pid
+            ));
+        }
+        ServerState::remove();
+    }
+    if detach {
+        return start_detached(build_mode).await;
+    }
+    // 🔒 Held until this process exits, so other foil commands can tell it's still this server running.
+    let lock = ServerLock::acquire()?;
+    let server = spawn_server(build_mode, false).await?;
+    supervise(server, lock).await
+}
+
+//=====================================================================================================================
+/// 👻 Start the foil server in a background process, returning once it's running.
+async fn start_detached(build_mode: BuildMode) -> Result<()> {
+    let config = config();
+    fs::create_dir_all(&config.log_dir)?;
+    let server_log = archive_log_path("server");
+    let log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&server_log)?;
+
+    // ⚙️ The background server is given the settings this command resolved, flags included.
+    let mut command = process::Command::new(env::current_exe()?);
+    command
+        .current_dir(env::current_dir()?)
+        .args(["server", "start"])
+        .arg(if build_mode == BuildMode::Development {
+            "--dev"
+        } else {
+            "--release"
+        })
+        .envs(config.server.env_vars())
+        .env(LOG_DIR, &config.log_dir)
+        .env(BUILD_CONCURRENCY, config.build_concurrency.to_string())
+        .env(SERVER_LOG, &server_log)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(log_file.try_clone()?))
+        .stderr(Stdio::from(log_file));
+    if let Some(path) = &config.path {
+        command.env(CONFIG_PATH, path);
+    }
+    if let Some(database_url) = &config.database_url {
+        command.env(DATABASE_URL, database_url);
+    }
+    // Its own process group keeps the terminal's Ctrl+C from reaching it.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x00000008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+    let mut child = command.spawn()?;
+
+    // ⏳ The server is running once it writes its state file.
+    let started = Instant::now();
+    loop {
+        if let Some(state) = ServerState::read() {
+            if state.supervisor_pid == child.id() {
                 println!(
-            "❌ Failed to write foil renderer.generated.js in current working directory, aborting."
-        );
+                    "👻 Foil server running in the background with PID {}.",
+                    child.id()
+                );
+                for address in state.listen.iter() {
+                    println!("🖥️ Listening on {}", address);
+                }
+                println!("🧻 Server output is written to {}", server_log.display());
                 return Ok(());
             }
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return err(&format!(
+                "❌ The Foil server exited with {}, see {}",
+                status,
+                server_log.display()
+            ));
+        }
+        if started.elapsed() >= DETACH_TIMEOUT {
+            return err(&format!(
+                "❌ The Foil server with PID {} didn't start within {:?}, see {}",
+                child.id(),
+                DETACH_TIMEOUT,
+                server_log.display()
+            ));
+        }
+        sleep(Duration::from_millis(250)).await;
+    }
+}
+
+//=====================================================================================================================
+/// 🛑 Stop the running foil server, killing it if it doesn't shut down in time.
+pub async fn stop_server() -> Result<()> {
+    let Some(state) = ServerState::read() else {
+        println!("💤 No Foil server is running.");
+        return Ok(());
+    };
+    if !state.is_running() {
+        println!(
+            "💤 No Foil server is running, the server with PID {} exited without cleaning up.",
+            state.supervisor_pid
+        );
+        ServerState::remove();
+        return Ok(());
+    }
+
+    println!(
+        "👋 Stopping the Foil server with PID {}...",
+        state.supervisor_pid
+    );
+    signal_process(state.supervisor_pid, false);
+    let asked = Instant::now();
+    while state.is_running() && asked.elapsed() < STOP_TIMEOUT {
+        sleep(Duration::from_millis(100)).await;
+    }
+    if state.is_running() {
+        println!(
+            "❌ The Foil server didn't stop within {:?}, killing it.",
+            STOP_TIMEOUT
+        );
+        for pid in [state.supervisor_pid, state.backend_pid, state.renderer_pid] {
+            signal_process(pid, true);
+        }
+    }
+    ServerState::remove();
+    println!("🛑 Foil server stopped.");
+    Ok(())
+}
+
+//=====================================================================================================================
+/// 🔁 Stop the running foil server if there is one, then start it again in the background.
+pub async fn restart_server(build_mode: BuildMode) -> Result<()> {
+    stop_server().await?;
+    start_server(build_mode, true).await
+}
+
+//=====================================================================================================================
+/// 🩺 Print the processes, uptime, addresses and log files of the running foil server, and whether its database is reachable.
+pub async fn server_status() -> Result<()> {
+    match ServerState::read() {
+        Some(state) if state.is_running() => {
+            let uptime = (Utc::now() - state.started_at).to_std().unwrap_or_default();
+            println!(
+                "🟢 Foil server running with PID {} for {}, in {}",
+                state.supervisor_pid,
+                format_uptime(uptime),
+                state.cwd.display()
+            );
+            println!(
+                "🦀 Backend PID {} ({})",
+                state.backend_pid,
+                process_status(state.backend_pid)
+            );
+            println!(
+                "🎨 Renderer PID {} ({}) on {}",
+                state.renderer_pid,
+                process_status(state.renderer_pid),
+                state.renderer_address
+            );
+            for address in state.listen.iter() {
+                println!("🖥️ Listening on {}", address);
+            }
+            if let Some(server_log) = &state.server_log {
+                println!("🧻 Server log: {}", server_log.display());
+            }
+            println!("🧻 Backend log: {}", state.backend_log.display());
+            println!("🧻 Renderer log: {}", state.renderer_log.display());
+        }
+        Some(state) => {
+            println!(
+                "🔴 Foil server isn't running, the server with PID {} exited without cleaning up.",
+                state.supervisor_pid
+            );
+            ServerState::remove();
+        }
+        None => println!("🔴 Foil server isn't running."),
+    }
+
+    // 📚 Whether or not the server is running, check the database it would use.
+    match timeout(DATABASE_CHECK_TIMEOUT, connect_db()).await {
+        Ok(Ok(_)) => println!("📚 Database is reachable."),
+        Ok(Err(e)) => println!("❌ Database is unreachable:\n{}", e),
+        Err(_) => println!(
+            "❌ Database is unreachable, connecting took over {:?}.",
+            DATABASE_CHECK_TIMEOUT
+        ),
+    }
+    Ok(())
+}
+
+fn process_status(pid: u32) -> &'static str {
+    if is_process_running(pid) {
+        "running"
+    } else {
+        "exited"
+    }
+}
+
+/// Format an uptime such as `2d 3h 4m 5s`, leaving out leading units that are zero.
+fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    let units = [
+        (seconds / 86400, "d"),
+        ((seconds / 3600) % 24, "h"),
+        ((seconds / 60) % 60, "m"),
+        (seconds % 60, "s"),
+    ];
+    let first = units.iter().position(|(v, _)| *v > 0).unwrap_or(3);
+    units[first..]
+        .iter()
+        .map(|(v, unit)| format!("{}{}", v, unit))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+//=====================================================================================================================
+/// Spawn the foil backend and server-side renderer, rendering in the given build mode.
+/// With `live_reload`, the browser reloads whenever the backend is told a foil was rebuilt.
+pub async fn spawn_server(build_mode: BuildMode, live_reload: bool) -> Result<FoilServer> {
+    // 🦀 Make sure the backend is there before doing anything else:
+    let backend_path = find_backend().await?;
+
+    // 📚 Configure database...
+    let pool = connect_db().await?;
+    check_schema(&pool).await?;
+
+    // ⚙️ Both processes are given the same settings from foil.toml, the environment and command line flags.
+    let server_config = config().server.clone();
+
+    // 🧻 Every start of the server keeps appending to the same logs, which are rotated as they grow and age.
+    fs::create_dir_all(&config().log_dir)?;
+    prune_logs();
+    let backend_log = RotatingLog::shared("backend")?;
+    let renderer_log = RotatingLog::shared("renderer")?;
+    let started_at = Utc::now();
+
+    // 🎨 Make sure there's a frontend to render before spawning anything:
+    let renderer = spawn_renderer(
+        &pool,
+        &server_config,
+        &build_mode,
+        live_reload,
+        &renderer_log,
+    )
+    .await?;
+
+    // 🌐 Spawn child processes for the server:
+    let backend = spawn_backend(&backend_path, &server_config, live_reload, &backend_log)?;
 
-            let cwd_node_modules = clean_path_string(&cwd.join("node_modules"));
-            let builder_node_modules = clean_path_string(&foil_builder_path.join("node_modules"));
-            let node_path_str = builder_node_modules + ";" + &cwd_node_modules;
-
-            let renderer_log_file = format!("foil-renderer-log-{}.txt", &date_now_string);
-            let renderer_log_file_abs = foil_log_path.clone().join(&renderer_log_file);
-            let renderer_file = File::create(renderer_log_file_abs).unwrap();
-            let renderer_stdio = Stdio::from(renderer_file);
-
-            let mut backend_renderer_child = process::Command::new("node")
-                .current_dir(&cwd)
-                .env("NODE_PATH", node_path_str)
-                .args([
-                    "--experimental-specifier-resolution=node",
-                    "--experimental-modules",
-                    "--experimental-import-meta-resolve",
-                    "--no-warnings",
-                    "--trace-warnings",
-                    &server_source_file_abs_str,
-                ])
-                .stderr(renderer_stdio)
-                .spawn()
-                .unwrap();
-
-            backend_renderer_child
-                .wait()
-                .expect("❌ Failed to run Foil Renderer...");
-            backend_server_child
-                .wait()
-                .expect("❌ Failed to run Foil Backend...");
+    Ok(FoilServer {
+        backend,
+        renderer,
+        config: server_config,
+        started_at,
+        backend_path,
+        pool,
+        build_mode,
+        live_reload,
+        backend_log,
+        renderer_log,
+    })
+}
+
+//=====================================================================================================================
+/// 🦀 Spawn the foil backend.
+fn spawn_backend(
+    backend_path: &PathBuf,
+    server_config: &ServerConfig,
+    live_reload: bool,
+    log: &SharedLog,
+) -> Result<process::Child> {
+    let cwd = env::current_dir().unwrap_or_default();
+    let foil_database_url = get_db_url();
+    let mut backend_command = process::Command::new(backend_path);
+    backend_command
+        .current_dir(&cwd)
+        .stdin(process::Stdio::null())
+        .env_remove("args")
+        .env(DATABASE_URL, foil_database_url)
+        .envs(server_config.env_vars())
+        .env("RUST_LOG", env::var("RUST_LOG").unwrap_or_default())
+        .stderr(Stdio::piped());
+    if live_reload {
+        backend_command.env(LIVE_RELOAD, "1");
+    }
+    let mut backend = match backend_command.spawn() {
+        Ok(backend) => backend,
+        Err(e) => {
+            return err(&format!(
+                "❌ Failed to start the Foil backend at {}:\n{}",
+                backend_path.display(),
+                e
+            ))
         }
+    };
+    if let Some(stderr) = backend.stderr.take() {
+        pipe_log(stderr, log.clone());
+    }
+    Ok(backend)
+}
+
+//=====================================================================================================================
+/// 🎨 Generate and spawn the foil server-side renderer for the root foil frontend.
+async fn spawn_renderer(
+    pool: &FoilPool,
+    server_config: &ServerConfig,
+    build_mode: &BuildMode,
+    live_reload: bool,
+    log: &SharedLog,
+) -> Result<process::Child> {
+    let cwd = env::current_dir().unwrap_or_default();
+
+    // 📦 Resolve Foil package.json from current working directory, attempt to run server from it:
+    let post = match query_post(pool, "/".to_string()).await {
+        Ok(post) => post,
         Err(e) => {
             println!("Failed to connect to database when starting server.\n{}", e);
-        },
+            return Err(e.into());
+        }
     };
-    Ok(())
+
+    // A few common vars:
+    let foil_builder_path = get_foil_builder_path();
+    let foil_cache_path = foil_builder_path.join(PathBuf::from("cache"));
+    fs::create_dir_all(&foil_cache_path)?;
+
+    // The Foil server-side renderer currently exists as a separate process:
+    let frontend_main = post.name.clone();
+    let main_path = clean_path_string(&PathBuf::from(&post.output_path).join("main.js"));
+    let mut import_map_str = format!("        \"{}\": \"file:///{}\",\n", &post.name, main_path);
+    for public_module in post.public_modules {
+        let mod_path = clean_path_string(
+            &PathBuf::from(&post.output_path).join(public_module.clone() + ".js"),
+        );
+        import_map_str += &format!(
+            "        \"{}\": \"file:///{}\",\n",
+            &public_module, mod_path
+        );
+    }
+    let server_src = format!(
+        include_str!("server-renderer-template.txt"),
+        frontend_main,
+        import_map_str,
+        live_reload,
+        include_str!("server-renderer.js")
+    );
+
+    // Due to the idiosyncrasies of Node.js and TypeScript, we must generate and load a renderer file:
+    // Also, due to the way node resolves modules, it must exist next to the frontend.
+    // We may want to clear this cache path in the future...
+
+    let server_source_file = frontend_main + "-renderer.generated.mjs";
+    let server_source_file_abs = foil_cache_path.clone().join(&server_source_file);
+    let server_source_file_abs_str = clean_path_string(&server_source_file_abs);
+
+    let write_result = fs::write(&server_source_file_abs, server_src);
+    if write_result.is_err() {
+        return err(
+            "❌ Failed to write foil renderer.generated.js in current working directory, aborting.",
+        );
+    }
+
+    let cwd_node_modules = clean_path_string(&cwd.join("node_modules"));
+    let builder_node_modules = clean_path_string(&foil_builder_path.join("node_modules"));
+    let node_path_str = builder_node_modules + ";" + &cwd_node_modules;
+
+    let mut renderer = process::Command::new("node")
+        .current_dir(&cwd)
+        .env("NODE_PATH", node_path_str)
+        .env("NODE_ENV", node_env(build_mode))
+        .envs(server_config.env_vars())
+        .args([
+            "--experimental-specifier-resolution=node",
+            "--experimental-modules",
+            "--experimental-import-meta-resolve",
+            "--no-warnings",
+            "--trace-warnings",
+            &server_source_file_abs_str,
+        ])
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 🧻 Restarted renderers keep writing to the same log.
+    if let Some(stderr) = renderer.stderr.take() {
+        pipe_log(stderr, log.clone());
+    }
+    Ok(renderer)
+}
+
+/// The `NODE_ENV` the renderer runs with, matching the build it renders.
+fn node_env(build_mode: &BuildMode) -> &str {
+    match build_mode {
+        BuildMode::Release => "production",
+        BuildMode::Development => "development",
+    }
 }
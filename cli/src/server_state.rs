@@ -0,0 +1,152 @@
+use crate::builder::get_foil_folder_path;
+use crate::error::{err, Result};
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+
+/// The file in the foil folder describing the running foil server.
+const SERVER_STATE_FILE: &str = "server-state.json";
+
+/// The file in the foil folder locked by the running foil server's supervisor.
+const SERVER_LOCK_FILE: &str = "server.lock";
+
+//=====================================================================================================================
+/// 📇 The processes, addresses and log files of a running foil server, so other foil commands can find it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerState {
+    /// The foil CLI process supervising the backend and renderer.
+    pub supervisor_pid: u32,
+    pub backend_pid: u32,
+    pub renderer_pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// The folder the server was started in.
+    pub cwd: PathBuf,
+    pub listen: Vec<String>,
+    pub renderer_address: String,
+    /// The output of the supervisor itself, if it was started with `--detach`.
+    pub server_log: Option<PathBuf>,
+    pub backend_log: PathBuf,
+    pub renderer_log: PathBuf,
+}
+
+impl ServerState {
+    /// Read the state of the running foil server, if there is one.
+    pub fn read() -> Option<ServerState> {
+        let source = fs::read_to_string(server_state_path()).ok()?;
+        serde_json::from_str(&source).ok()
+    }
+
+    /// Write this state for other foil commands to find.
+    pub fn write(&self) -> Result<()> {
+        let source = serde_json::to_string_pretty(self)?;
+        fs::write(server_state_path(), source)?;
+        Ok(())
+    }
+
+    /// Remove the state file, such as once the server has stopped.
+    pub fn remove() {
+        let _ = fs::remove_file(server_state_path());
+    }
+
+    /// Is the supervisor of this server still running?
+    /// Its PID alone could belong to an unrelated process after a crash or reboot, so it must also hold the server lock.
+    pub fn is_running(&self) -> bool {
+        ServerLock::is_held() && is_process_running(self.supervisor_pid)
+    }
+}
+
+/// The path of the foil server's state file.
+pub fn server_state_path() -> PathBuf {
+    get_foil_folder_path().join(SERVER_STATE_FILE)
+}
+
+//=====================================================================================================================
+/// 🔒 A lock the foil server's supervisor holds for as long as it runs, released by the OS however it exits.
+pub struct ServerLock {
+    _file: File,
+}
+
+impl ServerLock {
+    /// Lock the server for this process, failing if another foil server holds the lock.
+    pub fn acquire() -> Result<ServerLock> {
+        let file = open_server_lock()?;
+        match file.try_lock() {
+            Ok(()) => Ok(ServerLock { _file: file }),
+            Err(TryLockError::WouldBlock) => {
+                err("❌ A Foil server is already running, stop it with `foil server stop`.")
+            }
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// Is any process holding the server lock?
+    pub fn is_held() -> bool {
+        let Ok(file) = open_server_lock() else {
+            return false;
+        };
+        match file.try_lock() {
+            Ok(()) => {
+                let _ = file.unlock();
+                false
+            }
+            Err(TryLockError::WouldBlock) => true,
+            Err(TryLockError::Error(_)) => false,
+        }
+    }
+}
+
+fn open_server_lock() -> std::io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(get_foil_folder_path().join(SERVER_LOCK_FILE))
+}
+
+//=====================================================================================================================
+/// Is a process with this PID running?
+#[cfg(unix)]
+pub fn is_process_running(pid: u32) -> bool {
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    // 📡 Signal 0 only checks that the process exists.
+    match kill(Pid::from_raw(pid as i32), None) {
+        Ok(()) => true,
+        Err(e) => e == nix::errno::Errno::EPERM,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_process_running(pid: u32) -> bool {
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output();
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()),
+        Err(_) => false,
+    }
+}
+
+/// Ask a process to exit, or kill it outright with `force`.
+#[cfg(unix)]
+pub fn signal_process(pid: u32, force: bool) {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    let signal = if force {
+        Signal::SIGKILL
+    } else {
+        Signal::SIGTERM
+    };
+    let _ = kill(Pid::from_raw(pid as i32), signal);
+}
+
+#[cfg(not(unix))]
+pub fn signal_process(pid: u32, _force: bool) {
+    // Windows console processes can't be asked to exit by another console, so end the whole process tree.
+    let _ = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/T", "/F"])
+        .output();
+}
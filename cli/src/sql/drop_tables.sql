@@ -0,0 +1,13 @@
+DROP VIEW IF EXISTS posts_with_authors;
+
+DROP TABLE IF EXISTS post_authors;
+
+DROP TABLE IF EXISTS authors;
+
+DROP TABLE IF EXISTS posts;
+
+DROP TYPE IF EXISTS author CASCADE;
+
+DROP TYPE IF EXISTS redirect CASCADE;
+
+DROP TABLE IF EXISTS _sqlx_migrations;
@@ -1,4 +1,4 @@
-SELECT id, name, permalink, title, authors, description, keywords, rss, covers, date_published, root_path, output_path, public_modules
-FROM posts
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules
+FROM posts_with_authors
 WHERE LOWER(permalink) ~ $1
 ORDER BY date_published DESC
\ No newline at end of file
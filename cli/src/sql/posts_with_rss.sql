@@ -0,0 +1,4 @@
+SELECT id, name, permalink, title, authors, description, keywords, covers, main, assets, rss, feeds, date_published, date_modified, root_path, output_path, public_modules
+FROM posts_with_authors
+WHERE cardinality(rss) > 0
+ORDER BY permalink
\ No newline at end of file
@@ -0,0 +1,11 @@
+DROP VIEW IF EXISTS posts_with_authors;
+
+DROP TABLE IF EXISTS post_authors;
+
+DROP TABLE IF EXISTS authors;
+
+DROP TABLE IF EXISTS posts;
+
+DROP TABLE IF EXISTS foil_meta;
+
+DROP TABLE IF EXISTS _sqlx_migrations;
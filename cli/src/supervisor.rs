@@ -0,0 +1,244 @@
+use crate::error::{err, Result};
+use crate::server::FoilServer;
+use crate::server_state::{ServerLock, ServerState};
+use async_std::future::timeout;
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::TcpStream;
+use async_std::task::sleep;
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the supervisor checks on the backend and renderer.
+const SUPERVISE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the renderer is health checked over HTTP.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long the renderer may take to answer a health check.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a started renderer has to start answering before health checks count against it.
+const STARTUP_GRACE: Duration = Duration::from_secs(15);
+
+/// How many health checks in a row the renderer may fail before it's restarted.
+const MAX_HEALTH_FAILURES: u32 = 3;
+
+/// A process that stays up this long is considered started, resetting its backoff.
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+
+/// The first restart waits this long, doubling with every failure in a row.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How many times in a row a failing process is restarted before the server gives up.
+const MAX_RESTARTS: u32 = 5;
+
+/// How long processes have to exit after being asked to, before they're killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+//=====================================================================================================================
+/// The restart state of a supervised process.
+struct Supervised {
+    name: &'static str,
+    started: Instant,
+    /// How many times in a row this process failed before becoming stable.
+    failures: u32,
+    /// When this process crashed and should be restarted, once its backoff is over.
+    restart_at: Option<Instant>,
+    /// When this process was last health checked, or started.
+    last_health_check: Instant,
+    health_failures: u32,
+}
+
+impl Supervised {
+    fn new(name: &'static str) -> Supervised {
+        Supervised {
+            name,
+            started: Instant::now(),
+            failures: 0,
+            restart_at: None,
+            last_health_check: Instant::now(),
+            health_failures: 0,
+        }
+    }
+
+    /// Schedule a restart after a crash, or fail if this process keeps failing.
+    fn crashed(&mut self) -> Result<()> {
+        if self.started.elapsed() >= STABLE_AFTER {
+            self.failures = 0;
+        }
+        self.failures += 1;
+        if self.failures > MAX_RESTARTS {
+            return err(&format!(
+                "❌ The Foil {} failed {} times in a row, giving up after {} restarts.",
+                self.name, self.failures, MAX_RESTARTS
+            ));
+        }
+        let backoff = INITIAL_BACKOFF
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(MAX_BACKOFF);
+        println!(
+            "🔁 Restarting the Foil {} in {:.0?}, {} failure(s) in a row.",
+            self.name, backoff, self.failures
+        );
+        self.restart_at = Some(Instant::now() + backoff);
+        Ok(())
+    }
+
+    /// Is this process due to be restarted?
+    fn should_restart(&self) -> bool {
+        self.restart_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    fn restarted(&mut self) {
+        self.started = Instant::now();
+        self.restart_at = None;
+        self.last_health_check = Instant::now();
+        self.health_failures = 0;
+    }
+}
+
+//=====================================================================================================================
+/// 👷 Keep the foil backend and renderer running, restarting them with backoff whenever they crash or the renderer
+/// stops answering, until SIGINT or SIGTERM asks the server to shut down.
+pub async fn supervise(mut server: FoilServer, _lock: ServerLock) -> Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let handler_shutdown = shutdown.clone();
+    if let Err(e) = ctrlc::set_handler(move || handler_shutdown.store(true, Ordering::SeqCst)) {
+        println!("❌ Failed to listen for shutdown signals: {}", e);
+    }
+    write_state(&server);
+
+    let mut backend = Supervised::new("backend");
+    let mut renderer = Supervised::new("renderer");
+    let result = loop {
+        if shutdown.load(Ordering::SeqCst) {
+            println!("👋 Shutting down the Foil server...");
+            break Ok(());
+        }
+
+        // 💥 Report crashes, then restart each process once its backoff is over.
+        if backend.restart_at.is_none() {
+            if let Ok(Some(status)) = server.backend.try_wait() {
+                println!(
+                    "❌ Foil backend exited with {}, see {}",
+                    status,
+                    FoilServer::backend_log_path().display()
+                );
+                if let Err(e) = backend.crashed() {
+                    break Err(e);
+                }
+            }
+        }
+        if renderer.restart_at.is_none() {
+            if let Ok(Some(status)) = server.renderer.try_wait() {
+                println!(
+                    "❌ Foil renderer exited with {}, see {}",
+                    status,
+                    FoilServer::renderer_log_path().display()
+                );
+                if let Err(e) = renderer.crashed() {
+                    break Err(e);
+                }
+            }
+        }
+        if backend.should_restart() {
+            backend.restarted();
+            if let Err(e) = server.restart_backend() {
+                println!("❌ Failed to start the Foil backend:\n{}", e);
+                if let Err(e) = backend.crashed() {
+                    break Err(e);
+                }
+            }
+            write_state(&server);
+        }
+        if renderer.should_restart() {
+            renderer.restarted();
+            if let Err(e) = server.restart_renderer().await {
+                println!("❌ Failed to start the Foil renderer:\n{}", e);
+                if let Err(e) = renderer.crashed() {
+                    break Err(e);
+                }
+            }
+            write_state(&server);
+        }
+
+        // 🩺 A renderer that's running but no longer answering is restarted too.
+        if renderer.restart_at.is_none()
+            && renderer.started.elapsed() >= STARTUP_GRACE
+            && renderer.last_health_check.elapsed() >= HEALTH_CHECK_INTERVAL
+        {
+            renderer.last_health_check = Instant::now();
+            if check_renderer_health(&server.config.renderer_address).await {
+                renderer.health_failures = 0;
+            } else {
+                renderer.health_failures += 1;
+                if renderer.health_failures >= MAX_HEALTH_FAILURES {
+                    println!(
+                        "❌ Foil renderer failed {} health checks in a row.",
+                        MAX_HEALTH_FAILURES
+                    );
+                    let _ = server.renderer.kill();
+                    let _ = server.renderer.wait();
+                    if let Err(e) = renderer.crashed() {
+                        break Err(e);
+                    }
+                }
+            }
+        }
+
+        sleep(SUPERVISE_INTERVAL).await;
+    };
+
+    // 🛑 Ask both processes to exit, so in-flight requests can finish.
+    stop_process(&mut server.renderer).await;
+    stop_process(&mut server.backend).await;
+    ServerState::remove();
+    result
+}
+
+/// 📇 Record the current processes of the server, so other foil commands can find them.
+fn write_state(server: &FoilServer) {
+    if let Err(e) = server.state().write() {
+        println!("❌ Failed to write the Foil server state:\n{}", e);
+    }
+}
+
+//=====================================================================================================================
+/// 🩺 Check that the renderer answers HTTP requests, any status code means it's alive.
+async fn check_renderer_health(renderer_address: &str) -> bool {
+    let request = "GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+    let check = async {
+        let mut stream = TcpStream::connect(renderer_address).await?;
+        stream.write_all(request.as_bytes()).await?;
+        let mut status = [0u8; 5];
+        stream.read_exact(&mut status).await?;
+        Ok::<bool, std::io::Error>(&status == b"HTTP/")
+    };
+    matches!(timeout(HEALTH_CHECK_TIMEOUT, check).await, Ok(Ok(true)))
+}
+
+//=====================================================================================================================
+/// Gracefully stop a process with SIGTERM, killing it if it doesn't exit in time.
+async fn stop_process(child: &mut Child) {
+    if let Ok(Some(_)) = child.try_wait() {
+        return;
+    }
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+        let _ = kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM);
+        let asked = Instant::now();
+        while asked.elapsed() < SHUTDOWN_TIMEOUT {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
@@ -0,0 +1,241 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The environment variable with the path to foil's configuration file.
+pub const CONFIG_PATH: &str = "FOIL_CONFIG";
+
+/// The environment variable with the comma separated addresses the foil backend listens on.
+pub const LISTEN: &str = "FOIL_LISTEN";
+
+/// The environment variable with the address of the Node.js server-side renderer.
+pub const RENDERER_ADDRESS: &str = "FOIL_RENDERER_ADDRESS";
+
+/// The environment variable with how many seconds a request may take before the backend gives up on it.
+pub const REQUEST_TIMEOUT: &str = "FOIL_REQUEST_TIMEOUT";
+
+/// The environment variable with how many seconds the renderer may take to answer a page request.
+pub const RENDERER_TIMEOUT: &str = "FOIL_RENDERER_TIMEOUT";
+
+/// The environment variable with the public base URL of the site, used for absolute links.
+pub const SITE_URL: &str = "FOIL_SITE_URL";
+
+/// The configuration file looked for in the current directory and its parents when `FOIL_CONFIG` isn't set.
+pub const DEFAULT_CONFIG_FILE: &str = "foil.toml";
+
+const DEFAULT_LISTEN: &str = "127.0.0.1:4017";
+const DEFAULT_RENDERER_ADDRESS: &str = "127.0.0.1:4011";
+const DEFAULT_SITE_URL: &str = "http://localhost:4017";
+const DEFAULT_REQUEST_TIMEOUT: u64 = 300;
+const DEFAULT_RENDERER_TIMEOUT: u64 = 30;
+
+//=====================================================================================================================
+/// An invalid or unreadable foil configuration.
+#[derive(Debug)]
+pub struct ConfigError(pub String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+//=====================================================================================================================
+/// 👂 An address the foil backend listens on, either a TCP socket over IPv4 or IPv6, or a Unix socket.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListenAddress {
+    /// Such as `127.0.0.1:4017` or `[::1]:4017`.
+    Tcp(SocketAddr),
+    /// Such as `unix:/run/foil/backend.sock`.
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(ListenAddress::Unix(PathBuf::from(path)));
+        }
+        s.parse().map(ListenAddress::Tcp).map_err(|_| {
+            ConfigError(format!(
+                "Invalid listen address {}, expected an address such as 127.0.0.1:4017, [::1]:4017 or unix:/run/foil.sock.",
+                s
+            ))
+        })
+    }
+}
+
+impl fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ListenAddress::Tcp(addr) => write!(f, "{}", addr),
+            ListenAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+//=====================================================================================================================
+/// The `[server]` table of a foil configuration file, where every setting is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerFile {
+    listen: Option<Vec<String>>,
+    renderer_address: Option<String>,
+    request_timeout: Option<u64>,
+    renderer_timeout: Option<u64>,
+    site_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    server: ServerFile,
+}
+
+//=====================================================================================================================
+/// ⚙️ Settings for the foil backend and server-side renderer, shared by the foil CLI that spawns them.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// The configuration file these settings were read from, if any.
+    pub path: Option<PathBuf>,
+    /// Every address the backend listens on.
+    pub listen: Vec<ListenAddress>,
+    /// The `host:port` the server-side renderer listens on, which the backend proxies pages to.
+    pub renderer_address: String,
+    /// How long a request may take before the backend gives up on it.
+    pub request_timeout: Duration,
+    /// How long the renderer may take to answer a page request.
+    pub renderer_timeout: Duration,
+    /// The public base URL of the site, if configured.
+    pub site_url: Option<String>,
+}
+
+impl ServerConfig {
+    /// Load the configuration file found by `find_config_file`, overridden by any environment variables that are set.
+    pub fn load() -> Result<ServerConfig, ConfigError> {
+        ServerConfig::load_from(find_config_file())
+    }
+
+    /// Load a given configuration file, overridden by any environment variables that are set.
+    pub fn load_from(path: Option<PathBuf>) -> Result<ServerConfig, ConfigError> {
+        let file: ConfigFile = match &path {
+            Some(path) => read_config_file(path)?,
+            None => ConfigFile::default(),
+        };
+        let server = file.server;
+
+        let listen: Vec<String> = match env::var(LISTEN) {
+            Ok(v) => v.split(',').map(|s| s.to_string()).collect(),
+            Err(_) => server
+                .listen
+                .unwrap_or_else(|| vec![DEFAULT_LISTEN.to_string()]),
+        };
+        let listen = listen
+            .iter()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.parse())
+            .collect::<Result<Vec<ListenAddress>, ConfigError>>()?;
+        if listen.is_empty() {
+            return Err(ConfigError(
+                "The foil backend needs at least one listen address.".to_string(),
+            ));
+        }
+
+        let renderer_address = env::var(RENDERER_ADDRESS)
+            .ok()
+            .or(server.renderer_address)
+            .unwrap_or(DEFAULT_RENDERER_ADDRESS.to_string());
+        if !renderer_address.contains(':') {
+            return Err(ConfigError(format!(
+                "Invalid renderer address {}, expected a host and port such as 127.0.0.1:4011.",
+                renderer_address
+            )));
+        }
+
+        let request_timeout = env_seconds(REQUEST_TIMEOUT)?
+            .or(server.request_timeout)
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+        let renderer_timeout = env_seconds(RENDERER_TIMEOUT)?
+            .or(server.renderer_timeout)
+            .unwrap_or(DEFAULT_RENDERER_TIMEOUT);
+        let site_url = env::var(SITE_URL).ok().or(server.site_url);
+
+        Ok(ServerConfig {
+            path,
+            listen,
+            renderer_address,
+            request_timeout: Duration::from_secs(request_timeout),
+            renderer_timeout: Duration::from_secs(renderer_timeout),
+            site_url,
+        })
+    }
+
+    /// 🌐 The public base URL of the site without a trailing slash, `http://localhost:4017` if not configured.
+    pub fn site_url(&self) -> String {
+        self.site_url
+            .as_deref()
+            .unwrap_or(DEFAULT_SITE_URL)
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// These settings as environment variables, so processes spawned by the foil CLI use the same ones.
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let listen: Vec<String> = self.listen.iter().map(|l| l.to_string()).collect();
+        let mut vars = vec![
+            (LISTEN, listen.join(",")),
+            (RENDERER_ADDRESS, self.renderer_address.clone()),
+            (REQUEST_TIMEOUT, self.request_timeout.as_secs().to_string()),
+            (
+                RENDERER_TIMEOUT,
+                self.renderer_timeout.as_secs().to_string(),
+            ),
+        ];
+        if let Some(site_url) = &self.site_url {
+            vars.push((SITE_URL, site_url.clone()));
+        }
+        vars
+    }
+}
+
+/// Read a number of seconds from an environment variable, if it's set.
+fn env_seconds(var: &str) -> Result<Option<u64>, ConfigError> {
+    match env::var(var) {
+        Ok(v) => v.trim().parse().map(Some).map_err(|_| {
+            ConfigError(format!(
+                "Invalid {} {}, expected a number of seconds.",
+                var, v
+            ))
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+//=====================================================================================================================
+/// 🔎 Find foil's configuration file, from `FOIL_CONFIG` or the closest `foil.toml` in the current directory or its parents.
+pub fn find_config_file() -> Option<PathBuf> {
+    if let Ok(path) = env::var(CONFIG_PATH) {
+        return Some(PathBuf::from(path));
+    }
+    let cwd = env::current_dir().unwrap_or_default();
+    cwd.ancestors()
+        .map(|dir| dir.join(DEFAULT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Read and parse a foil configuration file, of which every caller picks the tables it understands.
+pub fn read_config_file<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| ConfigError(format!("Failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&source).map_err(|e| ConfigError(format!("Invalid {}: {}", path.display(), e)))
+}
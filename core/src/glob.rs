@@ -0,0 +1,264 @@
+use crate::clean_path_string;
+use regex::Regex;
+use std::path::PathBuf;
+
+/// Characters with a special meaning in both PostgreSQL and Rust regexes.
+const REGEX_META: &str = "\\.+*?()|[]{}^$";
+
+fn push_literal(regex: &mut String, c: char) {
+    if REGEX_META.contains(c) {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
+
+/// Find the `]` closing a character class opened at `start`, where a `]` right after `[` or `[!` is literal.
+fn find_class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if i < chars.len() && (chars[i] == '!' || chars[i] == '^') {
+        i += 1;
+    }
+    if i < chars.len() && chars[i] == ']' {
+        i += 1;
+    }
+    while i < chars.len() {
+        if chars[i] == ']' {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Does the `{` at `start` have a matching `}`?
+fn has_brace_end(chars: &[char], start: usize) -> bool {
+    let mut depth = 0;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    false
+}
+
+//=====================================================================================================================
+/// Convert a permalink glob to the regex used to query posts, understood by both PostgreSQL and SQLite.
+/// `*` and `?` stay within a single segment, `**` crosses segments, and `[abc]`, `[!abc]` and `{a,b}` are supported.
+pub fn permalink_glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = "^".to_string();
+    let mut brace_depth = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '*' => {
+                let mut end = i;
+                while end < chars.len() && chars[end] == '*' {
+                    end += 1;
+                }
+                if end - i == 1 {
+                    regex += "[^/]*";
+                    i = end;
+                    continue;
+                }
+                let segment_start = i == 0 || chars[i - 1] == '/';
+                let segment_end = end == chars.len() || chars[end] == '/';
+                if segment_start && end < chars.len() && chars[end] == '/' {
+                    // 🌳 `**/` matches zero or more whole segments.
+                    regex += "(.*/)?";
+                    i = end + 1;
+                    continue;
+                }
+                if segment_start && segment_end && i > 0 {
+                    // A trailing `/**` matches the folder itself, and everything in it.
+                    regex.pop();
+                    regex += "(/.*)?";
+                } else {
+                    regex += ".*";
+                }
+                i = end;
+                continue;
+            }
+            '?' => regex += "[^/]",
+            '[' => match find_class_end(&chars, i) {
+                Some(end) => {
+                    let mut j = i + 1;
+                    if chars[j] == '!' || chars[j] == '^' {
+                        // Negated classes still never match a segment separator.
+                        regex += "[^/";
+                        j += 1;
+                    } else {
+                        regex += "[";
+                    }
+                    while j < end {
+                        let class_char = chars[j];
+                        if class_char != '-' && !class_char.is_alphanumeric() {
+                            regex.push('\\');
+                        }
+                        regex.push(class_char);
+                        j += 1;
+                    }
+                    regex += "]";
+                    i = end;
+                }
+                None => push_literal(&mut regex, c),
+            },
+            '{' if has_brace_end(&chars, i) => {
+                regex += "(";
+                brace_depth += 1;
+            }
+            ',' if brace_depth > 0 => regex += "|",
+            '}' if brace_depth > 0 => {
+                regex += ")";
+                brace_depth -= 1;
+            }
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                push_literal(&mut regex, chars[i]);
+            }
+            _ => push_literal(&mut regex, c),
+        }
+        i += 1;
+    }
+    regex += "$";
+    regex
+}
+
+//=====================================================================================================================
+/// A compiled permalink glob, for matching paths outside of the database such as a foil's public assets.
+#[derive(Debug, Clone)]
+pub struct PermalinkGlob {
+    regex: Regex,
+}
+
+impl PermalinkGlob {
+    pub fn new(glob: &str) -> Result<PermalinkGlob, regex::Error> {
+        Ok(PermalinkGlob {
+            regex: Regex::new(&permalink_glob_to_regex(glob))?,
+        })
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+}
+
+//=====================================================================================================================
+/// 🤍 A rule of a foil's `assets` whitelist, where a leading `!` excludes the files it matches instead.
+#[derive(Debug, Clone)]
+pub struct AssetRule {
+    /// The rule as written in the foil's `package.json`.
+    pub rule: String,
+    pub negated: bool,
+    glob: PermalinkGlob,
+}
+
+impl AssetRule {
+    /// Compile an asset rule relative to the permalink or path of the foil it belongs to.
+    pub fn new(base: &str, rule: &str) -> Result<AssetRule, regex::Error> {
+        let (negated, asset_glob) = match rule.strip_prefix('!') {
+            Some(asset_glob) => (true, asset_glob),
+            None => (false, rule),
+        };
+        let full_glob = clean_path_string(&PathBuf::from(base).join(asset_glob));
+        Ok(AssetRule {
+            rule: rule.to_string(),
+            negated,
+            glob: PermalinkGlob::new(&full_glob)?,
+        })
+    }
+
+    /// Find the rule deciding whether a path is a public asset. Like a `.gitignore`, the last matching rule wins.
+    pub fn find_match<'a>(rules: &'a [AssetRule], path: &str) -> Option<&'a AssetRule> {
+        rules.iter().rev().find(|rule| rule.glob.matches(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(glob: &str, path: &str) -> bool {
+        PermalinkGlob::new(glob).unwrap().matches(path)
+    }
+
+    #[test]
+    fn star_stays_within_a_segment() {
+        assert!(matches("/blog/*", "/blog/a"));
+        assert!(!matches("/blog/*", "/blog/a/b"));
+        assert!(!matches("/blog/*", "/blog"));
+        assert!(matches("/blog/*.md", "/blog/post.md"));
+        assert!(!matches("/blog/*.md", "/blog/a/post.md"));
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        assert!(matches("/blog/**/*", "/blog/a"));
+        assert!(matches("/blog/**/*", "/blog/a/b/c"));
+        assert!(matches("/blog/**/post", "/blog/post"));
+        assert!(matches("/blog/**/post", "/blog/a/b/post"));
+        assert!(!matches("/blog/**/post", "/blog/a/b/other"));
+    }
+
+    #[test]
+    fn trailing_double_star_matches_the_folder_itself() {
+        assert!(matches("/blog/**", "/blog"));
+        assert!(matches("/blog/**", "/blog/a/b"));
+        assert!(!matches("/blog/**", "/blogs"));
+    }
+
+    #[test]
+    fn question_marks_and_classes_match_one_character() {
+        assert!(matches("/blog/post?", "/blog/post1"));
+        assert!(!matches("/blog/post?", "/blog/post/"));
+        assert!(matches("/blog/[ab]", "/blog/a"));
+        assert!(!matches("/blog/[ab]", "/blog/c"));
+        assert!(matches("/blog/[!ab]", "/blog/c"));
+        assert!(!matches("/blog/[!ab]", "/blog/a"));
+        assert!(!matches("/blog[!a]x", "/blog/x"));
+    }
+
+    #[test]
+    fn braces_are_alternatives() {
+        assert!(matches("/{blog,notes}/*", "/blog/a"));
+        assert!(matches("/{blog,notes}/*", "/notes/a"));
+        assert!(!matches("/{blog,notes}/*", "/talks/a"));
+        assert!(matches("/a/*.{png,jpg}", "/a/cover.jpg"));
+        assert!(matches("/a{b", "/a{b"));
+    }
+
+    #[test]
+    fn regex_metacharacters_are_literal() {
+        assert_eq!(permalink_glob_to_regex("/a.b+c"), "^/a\\.b\\+c$");
+        assert!(matches("/a.b", "/a.b"));
+        assert!(!matches("/a.b", "/axb"));
+        assert!(matches("/(draft)|$", "/(draft)|$"));
+        assert!(matches("/a\\*", "/a*"));
+        assert!(!matches("/a\\*", "/ab"));
+    }
+
+    #[test]
+    fn the_last_matching_asset_rule_wins() {
+        let rules: Vec<AssetRule> = ["assets/**/*", "!assets/private/**", "assets/private/ok.txt"]
+            .iter()
+            .map(|rule| AssetRule::new("/blog/post", rule).unwrap())
+            .collect();
+        let find = |path: &str| AssetRule::find_match(&rules, path).map(|rule| rule.negated);
+        assert_eq!(find("/blog/post/assets/cover.png"), Some(false));
+        assert_eq!(find("/blog/post/assets/private/key.txt"), Some(true));
+        assert_eq!(find("/blog/post/assets/private"), Some(true));
+        assert_eq!(find("/blog/post/assets/private/ok.txt"), Some(false));
+        assert_eq!(find("/blog/post/main.tsx"), None);
+    }
+}
@@ -0,0 +1,17 @@
+//! ✨ Foil's core library, shared by the foil CLI and backend so both interpret posts and permalinks the same way.
+
+pub mod config;
+pub mod glob;
+pub mod path;
+pub mod pool;
+pub mod post;
+pub mod redact;
+pub mod sitemap;
+
+pub use config::{ListenAddress, ServerConfig};
+pub use glob::{permalink_glob_to_regex, AssetRule, PermalinkGlob};
+pub use path::clean_path_string;
+pub use pool::FoilPool;
+pub use post::{Author, Authors, SQLPost, SocialLink};
+pub use redact::{redact_credentials, redact_db_url};
+pub use sitemap::absolute_url;
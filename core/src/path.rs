@@ -0,0 +1,12 @@
+use lexiclean::Lexiclean;
+use path_slash::PathBufExt;
+use std::path::Path;
+
+/// Resolve `.` and `..` in a path and write it with forward slashes, the form permalinks are stored in.
+pub fn clean_path_string(p: &Path) -> String {
+    p.lexiclean()
+        .to_slash()
+        .unwrap()
+        .to_string()
+        .replace("\\", "/")
+}
@@ -0,0 +1,34 @@
+use sqlx::{Pool, Postgres, Sqlite};
+
+//=====================================================================================================================
+/// A connection pool to Foil's database, either PostgreSQL or a SQLite file.
+#[derive(Debug, Clone)]
+pub enum FoilPool {
+    Postgres(Pool<Postgres>),
+    Sqlite(Pool<Sqlite>),
+}
+
+impl FoilPool {
+    pub fn is_sqlite(&self) -> bool {
+        matches!(self, FoilPool::Sqlite(_))
+    }
+}
+
+/// The PostgreSQL notification channel the foil CLI announces changed posts on, with newline separated permalinks.
+pub const POSTS_CHANNEL: &str = "foil_posts";
+
+/// Is this database URL a SQLite file, such as `sqlite://foil.db`?
+pub fn is_sqlite_url(db_url: &str) -> bool {
+    db_url.starts_with("sqlite:")
+}
+
+/// Run the same expression against whichever database a `FoilPool` is connected to.
+#[macro_export]
+macro_rules! match_pool {
+    ($pool:expr, $p:ident => $body:expr) => {
+        match $pool {
+            $crate::FoilPool::Postgres($p) => $body,
+            $crate::FoilPool::Sqlite($p) => $body,
+        }
+    };
+}
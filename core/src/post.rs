@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{FromRow, Row};
+
+//=====================================================================================================================
+/// 🎇 An author of a Foil post, stored once in the `authors` table and shared by every post that lists them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    pub name: String,
+    /// 🏷️ The URL-safe name of this author's page, derived from their name unless their `package.json` sets one.
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub url: String,
+    /// 🖼️ A URL to a picture of this author.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// 📝 A short biography of this author.
+    #[serde(default)]
+    pub bio: Option<String>,
+    /// 🔗 Links to this author's profiles elsewhere.
+    #[serde(default)]
+    pub socials: Vec<SocialLink>,
+}
+
+/// 🔗 A link to an author's profile on another site, such as `{ "name": "GitHub", "url": "https://github.com/..." }`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SocialLink {
+    pub name: String,
+    pub url: String,
+}
+
+/// The authors of a Foil post in the order its `package.json` lists them, read as a JSON array from `posts_with_authors`.
+#[derive(Serialize, Clone, Debug)]
+pub struct Authors(pub Vec<Author>);
+
+//=====================================================================================================================
+/// ✨ A Foil post as stored in the database, with every column of the `posts` table.
+#[derive(Serialize, Clone, Debug)]
+pub struct SQLPost {
+    /// 😎 ID for item.
+    pub id: i32,
+    /// The npm package name of this post.
+    pub name: String,
+    /// 📎 Permalink (eg. https://<your-blog>/{permalink}).
+    pub permalink: String,
+    /// 👋 Name of this item.
+    pub title: String,
+    /// The author(s) of this post.
+    pub authors: Authors,
+    /// 📝 Short description (120-240 characters) about the post.
+    pub description: String,
+    /// 🔎 Search keywords for this post.
+    pub keywords: Vec<String>,
+    /// 📑 Cover image url for this post.
+    pub covers: Vec<String>,
+    /// Main javascript file for this post with a default react component export.
+    pub main: String,
+    /// Globs of the public assets served from this post's folder.
+    pub assets: Vec<String>,
+    /// Permalink globs of the posts listed in this post's feed.
+    pub rss: Vec<String>,
+    /// The formats this post's feed is written in.
+    pub feeds: Vec<String>,
+    /// ⏰ The time this post was published.
+    pub date_published: DateTime<Utc>,
+    /// ⏱️ The time this post was updated.
+    pub date_modified: DateTime<Utc>,
+    /// The folder this post's `package.json` is in.
+    pub root_path: String,
+    /// The folder this post's build is written to.
+    pub output_path: String,
+    /// Output public modules exposed for systemJS.
+    pub public_modules: Vec<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for SQLPost {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        let authors: Json<Vec<Author>> = row.try_get("authors")?;
+        Ok(SQLPost {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            permalink: row.try_get("permalink")?,
+            title: row.try_get("title")?,
+            authors: Authors(authors.0),
+            description: row.try_get("description")?,
+            keywords: row.try_get("keywords")?,
+            covers: row.try_get("covers")?,
+            main: row.try_get("main")?,
+            assets: row.try_get("assets")?,
+            rss: row.try_get("rss")?,
+            feeds: row.try_get("feeds")?,
+            date_published: row.try_get("date_published")?,
+            date_modified: row.try_get("date_modified")?,
+            root_path: row.try_get("root_path")?,
+            output_path: row.try_get("output_path")?,
+            public_modules: row.try_get("public_modules")?,
+        })
+    }
+}
+
+/// SQLite has no arrays, so lists are stored as JSON.
+impl<'r> FromRow<'r, SqliteRow> for SQLPost {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let authors: Json<Vec<Author>> = row.try_get("authors")?;
+        let keywords: Json<Vec<String>> = row.try_get("keywords")?;
+        let covers: Json<Vec<String>> = row.try_get("covers")?;
+        let assets: Json<Vec<String>> = row.try_get("assets")?;
+        let rss: Json<Vec<String>> = row.try_get("rss")?;
+        let feeds: Json<Vec<String>> = row.try_get("feeds")?;
+        let public_modules: Json<Vec<String>> = row.try_get("public_modules")?;
+        Ok(SQLPost {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            permalink: row.try_get("permalink")?,
+            title: row.try_get("title")?,
+            authors: Authors(authors.0),
+            description: row.try_get("description")?,
+            keywords: keywords.0,
+            covers: covers.0,
+            main: row.try_get("main")?,
+            assets: assets.0,
+            rss: rss.0,
+            feeds: feeds.0,
+            date_published: row.try_get("date_published")?,
+            date_modified: row.try_get("date_modified")?,
+            root_path: row.try_get("root_path")?,
+            output_path: row.try_get("output_path")?,
+            public_modules: public_modules.0,
+        })
+    }
+}
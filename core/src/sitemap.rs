@@ -0,0 +1,194 @@
+use crate::{match_pool, FoilPool};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::postgres::PgRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::types::Json;
+use sqlx::{FromRow, Row};
+
+/// The most URLs a single sitemap may list, past which it's split into pages listed by a sitemap index.
+pub const SITEMAP_MAX_URLS: usize = 50000;
+
+//=====================================================================================================================
+/// 🗺️ A published post listed in the sitemap.
+#[derive(Clone, Debug)]
+pub struct SitemapPost {
+    pub permalink: String,
+    pub covers: Vec<String>,
+    pub date_modified: DateTime<Utc>,
+}
+
+impl<'r> FromRow<'r, PgRow> for SitemapPost {
+    fn from_row(row: &'r PgRow) -> Result<Self, sqlx::Error> {
+        Ok(SitemapPost {
+            permalink: row.try_get("permalink")?,
+            covers: row.try_get("covers")?,
+            date_modified: row.try_get("date_modified")?,
+        })
+    }
+}
+
+/// SQLite stores covers as a JSON array.
+impl<'r> FromRow<'r, SqliteRow> for SitemapPost {
+    fn from_row(row: &'r SqliteRow) -> Result<Self, sqlx::Error> {
+        let covers: Json<Vec<String>> = row.try_get("covers")?;
+        Ok(SitemapPost {
+            permalink: row.try_get("permalink")?,
+            covers: covers.0,
+            date_modified: row.try_get("date_modified")?,
+        })
+    }
+}
+
+/// Query every published post in the order the sitemap lists them.
+pub async fn query_sitemap_posts(pool: &FoilPool) -> Result<Vec<SitemapPost>, sqlx::Error> {
+    match_pool!(pool, p => {
+        sqlx::query_as(include_str!("sql/posts_sitemap.sql"))
+            .bind(Utc::now())
+            .fetch_all(p)
+            .await
+    })
+}
+
+//=====================================================================================================================
+/// 🌐 Make a permalink absolute to the site's base URL, leaving URLs that already have a scheme untouched.
+pub fn absolute_url(site_url: &str, permalink: &str) -> String {
+    if permalink.contains("://") {
+        return permalink.to_string();
+    }
+    if permalink.starts_with('/') {
+        site_url.to_string() + permalink
+    } else {
+        site_url.to_string() + "/" + permalink
+    }
+}
+
+/// Escape text written into XML elements.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn w3c_date(date: &DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+//=====================================================================================================================
+/// Write a sitemap listing every given post, with its covers as image entries.
+pub fn write_urlset(site_url: &str, posts: &[SitemapPost]) -> String {
+    let mut sitemap = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    sitemap += "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\" xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\">\n";
+    for post in posts {
+        sitemap += "  <url>\n";
+        sitemap += &format!(
+            "    <loc>{}</loc>\n",
+            escape_xml(&absolute_url(site_url, &post.permalink))
+        );
+        sitemap += &format!("    <lastmod>{}</lastmod>\n", w3c_date(&post.date_modified));
+        for cover in post.covers.iter() {
+            sitemap += &format!(
+                "    <image:image>\n      <image:loc>{}</image:loc>\n    </image:image>\n",
+                escape_xml(&absolute_url(site_url, cover))
+            );
+        }
+        sitemap += "  </url>\n";
+    }
+    sitemap += "</urlset>\n";
+    sitemap
+}
+
+//=====================================================================================================================
+/// Write a sitemap index listing every sitemap page, each modified when its latest post was.
+pub fn write_sitemap_index(site_url: &str, pages: &[&[SitemapPost]]) -> String {
+    let mut sitemap = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string();
+    sitemap += "<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n";
+    for (i, page) in pages.iter().enumerate() {
+        let page_url = absolute_url(site_url, &sitemap_page_file_name(i + 1));
+        sitemap += "  <sitemap>\n";
+        sitemap += &format!("    <loc>{}</loc>\n", escape_xml(&page_url));
+        if let Some(last_modified) = page.iter().map(|post| post.date_modified).max() {
+            sitemap += &format!("    <lastmod>{}</lastmod>\n", w3c_date(&last_modified));
+        }
+        sitemap += "  </sitemap>\n";
+    }
+    sitemap += "</sitemapindex>\n";
+    sitemap
+}
+
+/// The file name of a sitemap page, starting from 1.
+pub fn sitemap_page_file_name(page: usize) -> String {
+    format!("sitemap-{}.xml", page)
+}
+
+//=====================================================================================================================
+/// 🗺️ Write the sitemap of every given post as file name and contents pairs, `sitemap.xml` first.
+/// Sitemaps past 50,000 URLs are split into pages listed by `sitemap.xml`.
+pub fn write_sitemap(site_url: &str, posts: &[SitemapPost]) -> Vec<(String, String)> {
+    if posts.len() <= SITEMAP_MAX_URLS {
+        return vec![("sitemap.xml".to_string(), write_urlset(site_url, posts))];
+    }
+    let pages: Vec<&[SitemapPost]> = posts.chunks(SITEMAP_MAX_URLS).collect();
+    let mut files = vec![(
+        "sitemap.xml".to_string(),
+        write_sitemap_index(site_url, &pages),
+    )];
+    for (i, page) in pages.iter().enumerate() {
+        files.push((sitemap_page_file_name(i + 1), write_urlset(site_url, page)));
+    }
+    files
+}
+
+/// The posts listed by a page of a split sitemap, starting from 1, or `None` if the sitemap has no such page.
+pub fn sitemap_page(posts: &[SitemapPost], page: usize) -> Option<&[SitemapPost]> {
+    if page == 0 || posts.len() <= SITEMAP_MAX_URLS {
+        return None;
+    }
+    posts.chunks(SITEMAP_MAX_URLS).nth(page - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(permalink: &str) -> SitemapPost {
+        SitemapPost {
+            permalink: permalink.to_string(),
+            covers: vec!["assets/cover.png".to_string()],
+            date_modified: DateTime::from_timestamp(0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn urls_are_absolute_to_the_site() {
+        assert_eq!(absolute_url("https://a.io", "/blog"), "https://a.io/blog");
+        assert_eq!(absolute_url("https://a.io", "blog"), "https://a.io/blog");
+        assert_eq!(
+            absolute_url("https://a.io", "https://b.io/x"),
+            "https://b.io/x"
+        );
+    }
+
+    #[test]
+    fn urlsets_escape_urls() {
+        let sitemap = write_urlset("https://a.io", &[post("/q&a")]);
+        assert!(sitemap.contains("<loc>https://a.io/q&amp;a</loc>"));
+        assert!(sitemap.contains("<image:loc>https://a.io/assets/cover.png</image:loc>"));
+        assert!(sitemap.contains("<lastmod>1970-01-01T00:00:00Z</lastmod>"));
+    }
+
+    #[test]
+    fn large_sitemaps_are_split_into_pages() {
+        let posts: Vec<SitemapPost> = (0..SITEMAP_MAX_URLS + 1)
+            .map(|i| post(&format!("/{}", i)))
+            .collect();
+        let files = write_sitemap("https://a.io", &posts);
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["sitemap.xml", "sitemap-1.xml", "sitemap-2.xml"]);
+        assert!(files[0].1.contains("<loc>https://a.io/sitemap-2.xml</loc>"));
+        assert_eq!(sitemap_page(&posts, 2).map(|p| p.len()), Some(1));
+        assert!(sitemap_page(&posts, 3).is_none());
+        assert!(sitemap_page(&posts[..1], 1).is_none());
+    }
+}
@@ -0,0 +1,4 @@
+SELECT permalink, covers, date_modified
+FROM posts
+WHERE date_published <= $1
+ORDER BY permalink
\ No newline at end of file